@@ -0,0 +1,124 @@
+use std::io::{self, Write};
+
+/// Colores de cada parte de la salida, como secuencias SGR ("01;31") sin el "\x1b[" ni la "m".
+/// Un color vacio deja esa parte sin colorear
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Colors {
+    pub selected_match: String, // ms: matches en las lineas seleccionadas
+    pub context_match: String,  // mc: matches en las lineas de contexto
+    pub selected_line: String,  // sl: resto de las lineas seleccionadas
+    pub context_line: String,   // cx: resto de las lineas de contexto
+    pub file_name: String,      // fn
+    pub line_number: String,    // ln: tambien para la columna
+    pub byte_offset: String,    // bn
+    pub separator: String,      // se: separadores de los prefijos y de los grupos
+    pub reverse: bool,          // rv: con -v, sl y cx se intercambian
+    pub no_erase: bool,         // ne: no borrar hasta el final de la linea con "\x1b[K"
+}
+
+impl Default for Colors {
+    /// Los mismos colores que GNU grep
+    fn default() -> Self {
+        Colors {
+            selected_match: "01;31".to_string(),
+            context_match: "01;31".to_string(),
+            selected_line: String::new(),
+            context_line: String::new(),
+            file_name: "35".to_string(),
+            line_number: "32".to_string(),
+            byte_offset: "32".to_string(),
+            separator: "36".to_string(),
+            reverse: false,
+            no_erase: false,
+        }
+    }
+}
+
+impl Colors {
+    /// PARSE: lee una especificacion con el formato de GREP_COLORS ("ms=01;31:fn=35:ne") a
+    /// partir de los colores por defecto. Las claves desconocidas y los valores que no son
+    /// secuencias SGR se ignoran, como en GNU grep
+    pub fn parse(spec: &str) -> Colors {
+        let mut colors = Colors::default();
+        for entry in spec.split(':') {
+            let (key, value) = match entry.split_once('=') {
+                Some((key, value)) if is_sgr(value) => (key, Some(value.to_string())),
+                Some(_) => continue,
+                None => (entry, None),
+            };
+
+            match (key, value) {
+                ("mt", Some(value)) => {
+                    colors.selected_match = value.clone();
+                    colors.context_match = value;
+                }
+                ("ms", Some(value)) => colors.selected_match = value,
+                ("mc", Some(value)) => colors.context_match = value,
+                ("sl", Some(value)) => colors.selected_line = value,
+                ("cx", Some(value)) => colors.context_line = value,
+                ("fn", Some(value)) => colors.file_name = value,
+                ("ln", Some(value)) => colors.line_number = value,
+                ("bn", Some(value)) => colors.byte_offset = value,
+                ("se", Some(value)) => colors.separator = value,
+                ("rv", None) => colors.reverse = true,
+                ("ne", None) => colors.no_erase = true,
+                _ => {}
+            }
+        }
+        colors
+    }
+
+    /// LINE_COLORS: colores de las lineas seleccionadas y de contexto, intercambiados si
+    /// corresponde por rv
+    pub fn line_colors(&self, invert_match: bool) -> (&str, &str) {
+        match self.reverse && invert_match {
+            true => (&self.context_line, &self.selected_line),
+            false => (&self.selected_line, &self.context_line),
+        }
+    }
+
+    /// PAINT: escribe `text` con el color `sgr`. Si el color es vacio o no hay texto, lo
+    /// escribe tal cual
+    pub fn paint<W: Write>(&self, out: &mut W, sgr: &str, text: &[u8]) -> io::Result<()> {
+        if sgr.is_empty() || text.is_empty() {
+            return out.write_all(text);
+        }
+        let erase = if self.no_erase { "" } else { "\x1b[K" };
+        write!(out, "\x1b[{}m{}", sgr, erase)?;
+        out.write_all(text)?;
+        write!(out, "\x1b[m{}", erase)
+    }
+}
+
+/// IS_SGR: indica si un valor es una secuencia de parametros SGR: numeros separados por ';'
+fn is_sgr(value: &str) -> bool {
+    value.bytes().all(|b| b.is_ascii_digit() || b == b';')
+}
+
+/// STDOUT_IS_TERMINAL: indica si la salida estandar es una terminal
+pub fn stdout_is_terminal() -> bool {
+    sys::is_terminal(1)
+}
+
+/// ISATTY de la libc, el mismo chequeo que hace GNU grep para --color=auto. Fuera de unix la
+/// salida nunca se considera una terminal
+#[cfg(unix)]
+mod sys {
+    use std::ffi::c_int;
+
+    extern "C" {
+        fn isatty(fd: c_int) -> c_int;
+    }
+
+    pub fn is_terminal(fd: c_int) -> bool {
+        // SAFETY: isatty solo consulta el descriptor, aunque no este abierto
+        unsafe { isatty(fd) == 1 }
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    pub fn is_terminal(_fd: i32) -> bool {
+        false
+    }
+}
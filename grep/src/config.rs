@@ -0,0 +1,743 @@
+use std::fmt;
+
+pub const USAGE: &str = "Usage: grep [OPTION]... PATTERNS [FILE]...";
+
+pub const HELP: &str = "\
+Usage: grep [OPTION]... PATTERNS [FILE]...
+Search for PATTERNS in each FILE.
+Example: grep -F 'hello world' menu.h main.c
+PATTERNS can contain multiple patterns separated by newlines.
+When FILE is '-', read standard input.  With no FILE, read '.' if
+recursive, '-' otherwise.
+
+Pattern selection and interpretation:
+  -E, --extended-regexp     PATTERNS are extended regular expressions (default)
+  -F, --fixed-strings       PATTERNS are strings
+  -e, --regexp=PATTERNS     use PATTERNS for matching
+  -f, --file=FILE           take PATTERNS from FILE
+  -i, --ignore-case         ignore case distinctions in patterns and data
+  -w, --word-regexp         match only whole words
+  -x, --line-regexp         match only whole lines
+  -U, --multiline           allow matches to span more than one line
+  -v, --invert-match        select non-matching lines
+
+File and directory selection:
+  -a, --text                equivalent to --binary-files=text
+  -I                        equivalent to --binary-files=without-match
+      --binary-files=TYPE   assume that binary files are TYPE;
+                            TYPE is 'binary', 'text', or 'without-match'
+  -r, --recursive           search directories recursively
+  -R, --dereference-recursive  likewise, but follow all symlinks
+      --include=GLOB        search only files that match GLOB
+      --exclude=GLOB        skip files that match GLOB
+      --exclude-dir=GLOB    skip directories that match GLOB
+      --max-depth=NUM       descend at most NUM directories below each FILE
+      --no-ignore           don't skip files listed in .gitignore, .ignore
+                            and .git/info/exclude when recursing
+      --hidden              search hidden files and directories when recursing
+
+Output line prefix control:
+  -H, --with-filename       print file name with output lines
+  -h, --no-filename         suppress the file name prefix on output
+      --label=LABEL         use LABEL as the standard input file name prefix
+  -b, --byte-offset         print the byte offset with output lines
+  -n, --line-number         print line number with output lines
+      --column              print the column of the first match (1-based)
+      --vimgrep             print one 'FILE:LINE:COLUMN:TEXT' row per match
+      --format=TEMPLATE     print each match with TEMPLATE, using {path}, {line},
+                            {col}, {byte}, {text}, {match} and groups {N} or {NAME}
+      --color=WHEN          use markers to highlight the matching strings;
+                            WHEN is 'always', 'never', or 'auto'
+
+Output control:
+  -m, --max-count=NUM       stop after NUM selected lines
+  -o, --only-matching       show only nonempty parts of lines that match
+  -c, --count               print only a count of selected lines per FILE
+  -q, --quiet               suppress all normal output; exit after the first match
+  -l, --files-with-matches  print only names of FILEs with selected lines
+  -L, --files-without-match print only names of FILEs with no selected lines
+  -Z, --null                print 0 byte after FILE name
+      --json                print results as JSON Lines
+
+Context control:
+  -B, --before-context=NUM  print NUM lines of leading context
+  -A, --after-context=NUM   print NUM lines of trailing context
+  -C, --context=NUM         print NUM lines of output context
+      --group-separator=SEP  print SEP on line between matches with context
+      --no-group-separator  do not print separator for matches with context
+
+Performance:
+  -j, --threads=NUM         search NUM files at a time (default: number of cores)
+      --mmap                search files through memory maps when possible
+      --no-mmap             never use memory maps
+                            (default: only for a few large files)
+      --sort=KEY            print results in a fixed order; KEY is 'path' (order
+                            of the FILE operands, alphabetical inside
+                            directories) or 'none'
+
+Miscellaneous:
+  -s, --no-messages         suppress error messages about unreadable files
+  -V, --version             display version information and exit
+      --help                display this help text and exit
+
+Exit status is 0 if any line is selected, 1 otherwise;
+if any error occurs and -q is not given, the exit status is 2.
+";
+
+/// Configuracion de una busqueda, armada a partir de los argumentos
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    pub patterns: Vec<String>,      // Patrones de -e o el primer argumento
+    pub pattern_files: Vec<String>, // Archivos de -f
+    pub paths: Vec<String>,         // Vacio o "-" para la entrada estandar
+    pub fixed_strings: bool,
+    pub ignore_case: bool, // -i: sin distinguir mayusculas de minusculas
+    pub word_regexp: bool, // -w: los matches tienen que ser palabras enteras
+    pub line_regexp: bool, // -x: los matches tienen que ser lineas enteras; gana sobre -w
+    pub multiline: bool,   // -U: un match puede abarcar varias lineas
+    pub invert_match: bool,
+    pub count: bool,
+    pub only_matching: bool, // -o: cada match en su propia linea, sin el resto
+    pub quiet: bool,
+    pub max_count: Option<u64>, // Lineas seleccionadas por archivo antes de dejar de leer
+    pub files_with_matches: bool, // -l
+    pub files_without_match: bool, // -L
+    pub line_number: bool,
+    pub byte_offset: bool,
+    pub column: bool,
+    pub vimgrep: bool,          // Una fila "archivo:linea:columna:texto" por match
+    pub null: bool,             // -Z: los nombres terminan en '\0' en lugar de ':' o '\n'
+    pub json: bool,             // Un objeto JSON por evento; gana sobre los otros formatos
+    pub format: Option<String>, // Plantilla para cada match, ver `Template`
+    pub color: ColorChoice,
+    pub binary_files: BinaryFiles,
+    pub before_context: Option<usize>, // -B; si no, el de -C
+    pub after_context: Option<usize>,  // -A; si no, el de -C
+    pub context: Option<usize>,
+    pub group_separator: Option<String>, // Si no se indica, "--"
+    pub no_group_separator: bool,
+    pub with_filename: Option<bool>, // -H o -h; si no, depende de la cantidad de archivos
+    pub label: Option<String>,       // Nombre de la entrada estandar
+    pub recursive: bool,
+    pub dereference: bool, // -R: seguir todos los enlaces, no solo los indicados
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub exclude_dir: Vec<String>,
+    pub max_depth: Option<usize>,
+    pub no_ignore: bool,
+    pub hidden: bool,
+    pub threads: Option<usize>, // Si no se indica, uno por nucleo
+    pub sort: SortBy,
+    pub mmap: Option<bool>, // --mmap o --no-mmap; si no, se decide por archivo
+    pub no_messages: bool,  // -s: no informar los archivos que no se pueden leer
+}
+
+/// Orden de los resultados de varios archivos
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortBy {
+    #[default]
+    None, // En el orden en que terminan los hilos
+    Path,
+}
+
+/// Cuando resaltar la salida con colores
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    #[default]
+    Never,
+    Always,
+    Auto, // Solo si la salida es una terminal
+}
+
+/// Que hacer con los archivos binarios: los que tienen un byte NUL o no son UTF-8 valido al
+/// principio
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BinaryFiles {
+    #[default]
+    Binary, // Solo se avisa que el archivo matchea, sin escribir las lineas
+    Text,         // Se buscan como cualquier otro archivo
+    WithoutMatch, // Se considera que no matchean
+}
+
+impl Config {
+    /// SHOW_FILENAME: indica si cada resultado va precedido del nombre del archivo
+    pub fn show_filename(&self) -> bool {
+        self.with_filename
+            .unwrap_or(self.paths.len() > 1 || self.recursive || self.vimgrep)
+    }
+
+    /// BEFORE_CONTEXT: lineas de contexto antes de cada resultado; -B tiene prioridad sobre -C
+    pub fn before_context(&self) -> usize {
+        self.before_context.or(self.context).unwrap_or(0)
+    }
+
+    /// AFTER_CONTEXT: lineas de contexto despues de cada resultado; -A tiene prioridad sobre -C
+    pub fn after_context(&self) -> usize {
+        self.after_context.or(self.context).unwrap_or(0)
+    }
+
+    /// GROUP_SEPARATOR: linea que separa grupos de resultados no contiguos, tambien los de
+    /// archivos distintos. Solo se escribe si se pidio contexto, aunque sea de 0 lineas, y se
+    /// escriben las lineas (con -o tambien, aunque no se escriba el contexto). Con --json y con
+    /// --format no se escribe, cada registro lleva su posicion
+    pub fn group_separator(&self) -> Option<&str> {
+        let context = self.before_context.or(self.after_context).or(self.context);
+        let summary = self.quiet
+            || self.count
+            || self.files_with_matches
+            || self.files_without_match
+            || self.json
+            || self.format.is_some();
+        match context.is_some() && !summary && !self.no_group_separator {
+            true => Some(self.group_separator.as_deref().unwrap_or("--")),
+            false => None,
+        }
+    }
+
+    /// DISPLAY_NAME: nombre con el que se muestra un archivo, "-" es la entrada estandar
+    pub fn display_name<'a>(&'a self, path: &'a str) -> &'a str {
+        match path {
+            "-" => self.label.as_deref().unwrap_or("(standard input)"),
+            _ => path,
+        }
+    }
+}
+
+/// Lo que pidio el usuario: buscar o mostrar la ayuda o la version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Search(Box<Config>),
+    Help,
+    Version,
+}
+
+/// Errores al leer los argumentos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    UnknownShortOption(char),
+    UnknownLongOption(String),
+    AmbiguousOption(String, Vec<&'static str>),
+    MissingValue(String),
+    UnexpectedValue(String),
+    InvalidValue(&'static str, String),
+    MissingPattern,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::UnknownShortOption(c) => write!(f, "invalid option -- '{}'", c),
+            ConfigError::UnknownLongOption(name) => write!(f, "unrecognized option '{}'", name),
+            ConfigError::AmbiguousOption(name, candidates) => {
+                write!(f, "option '{}' is ambiguous; possibilities:", name)?;
+                for candidate in candidates {
+                    write!(f, " '--{}'", candidate)?;
+                }
+                Ok(())
+            }
+            // Como GNU, el mensaje depende de si la opcion se escribio larga o corta
+            ConfigError::MissingValue(name) => match name.starts_with("--") {
+                true => write!(f, "option '{}' requires an argument", name),
+                false => write!(f, "option requires an argument -- '{}'", name),
+            },
+            ConfigError::UnexpectedValue(name) => {
+                write!(f, "option '--{}' doesn't allow an argument", name)
+            }
+            ConfigError::InvalidValue(name, value) => {
+                write!(f, "invalid argument '{}' for '--{}'", value, name)
+            }
+            ConfigError::MissingPattern => write!(f, "no pattern given"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Descripcion de una opcion: su nombre largo, su letra (si tiene) y si recibe un valor
+struct OptionSpec {
+    long: &'static str,
+    short: Option<char>,
+    takes_value: bool,
+}
+
+const OPTIONS: &[OptionSpec] = &[
+    OptionSpec {
+        long: "after-context",
+        short: Some('A'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "before-context",
+        short: Some('B'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "binary-files",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "byte-offset",
+        short: Some('b'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "color",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "column",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "context",
+        short: Some('C'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "count",
+        short: Some('c'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "dereference-recursive",
+        short: Some('R'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "exclude",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "exclude-dir",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "extended-regexp",
+        short: Some('E'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "file",
+        short: Some('f'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "files-with-matches",
+        short: Some('l'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "files-without-match",
+        short: Some('L'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "fixed-strings",
+        short: Some('F'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "format",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "group-separator",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "help",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "hidden",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "ignore-case",
+        short: Some('i'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "include",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "invert-match",
+        short: Some('v'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "json",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "label",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "line-number",
+        short: Some('n'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "line-regexp",
+        short: Some('x'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "max-count",
+        short: Some('m'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "max-depth",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "mmap",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "multiline",
+        short: Some('U'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "no-filename",
+        short: Some('h'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "no-group-separator",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "no-ignore",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "no-messages",
+        short: Some('s'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "no-mmap",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "null",
+        short: Some('Z'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "only-matching",
+        short: Some('o'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "quiet",
+        short: Some('q'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "recursive",
+        short: Some('r'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "regexp",
+        short: Some('e'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "sort",
+        short: None,
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "text",
+        short: Some('a'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "threads",
+        short: Some('j'),
+        takes_value: true,
+    },
+    OptionSpec {
+        long: "version",
+        short: Some('V'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "vimgrep",
+        short: None,
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "with-filename",
+        short: Some('H'),
+        takes_value: false,
+    },
+    OptionSpec {
+        long: "word-regexp",
+        short: Some('w'),
+        takes_value: false,
+    },
+];
+
+/// Letras sin opcion larga propia, que equivalen a una opcion larga con un valor fijo
+const SHORT_ALIASES: &[(char, &str, &str)] = &[('I', "binary-files", "without-match")];
+
+/// PARSE_ARGS: interpreta los argumentos (sin el nombre del programa) como GNU grep.
+/// Acepta opciones agrupadas ("-Fe PAT"), valores pegados ("-ePAT", "--regexp=PAT") y "--"
+/// para terminar las opciones
+pub fn parse_args<I, S>(args: I) -> Result<Command, ConfigError>
+where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+{
+    let mut args = args.into_iter().map(Into::into);
+    let mut config = Config::default();
+    let mut positional: Vec<String> = vec![];
+    let mut pattern_given = false;
+
+    while let Some(arg) = args.next() {
+        if arg == "--" {
+            positional.extend(args.by_ref());
+            break;
+        }
+
+        if let Some(long) = arg.strip_prefix("--") {
+            let (name, attached) = match long.split_once('=') {
+                Some((name, value)) => (name, Some(value.to_string())),
+                None => (long, None),
+            };
+            let spec = find_long(name)?;
+
+            let value = match (spec.takes_value, attached) {
+                (true, Some(value)) => Some(value),
+                (true, None) => Some(
+                    args.next()
+                        .ok_or_else(|| ConfigError::MissingValue(format!("--{}", spec.long)))?,
+                ),
+                (false, Some(_)) => {
+                    return Err(ConfigError::UnexpectedValue(spec.long.to_string()))
+                }
+                (false, None) => None,
+            };
+
+            if let Some(command) = apply(spec, value, &mut config, &mut pattern_given)? {
+                return Ok(command);
+            }
+        } else if arg.len() > 1 && arg.starts_with('-') {
+            let flags: Vec<char> = arg[1..].chars().collect();
+            for (i, flag) in flags.iter().enumerate() {
+                if let Some((_, long, value)) = SHORT_ALIASES.iter().find(|(c, ..)| c == flag) {
+                    let spec = find_long(long)?;
+                    apply(
+                        spec,
+                        Some(value.to_string()),
+                        &mut config,
+                        &mut pattern_given,
+                    )?;
+                    continue;
+                }
+
+                let spec = OPTIONS
+                    .iter()
+                    .find(|spec| spec.short == Some(*flag))
+                    .ok_or(ConfigError::UnknownShortOption(*flag))?;
+
+                // Una opcion con valor se lleva el resto del grupo o el argumento siguiente
+                let value = if spec.takes_value {
+                    let rest: String = flags[i + 1..].iter().collect();
+                    if rest.is_empty() {
+                        Some(
+                            args.next()
+                                .ok_or(ConfigError::MissingValue(flag.to_string()))?,
+                        )
+                    } else {
+                        Some(rest)
+                    }
+                } else {
+                    None
+                };
+
+                let takes_value = spec.takes_value;
+                if let Some(command) = apply(spec, value, &mut config, &mut pattern_given)? {
+                    return Ok(command);
+                }
+                if takes_value {
+                    break;
+                }
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    if !pattern_given {
+        config
+            .patterns
+            .push(positional.next().ok_or(ConfigError::MissingPattern)?);
+    }
+    config.paths.extend(positional);
+
+    Ok(Command::Search(Box::new(config)))
+}
+
+/// FIND_LONG: busca una opcion larga por su nombre o por un prefijo que no sea ambiguo
+fn find_long(name: &str) -> Result<&'static OptionSpec, ConfigError> {
+    if let Some(spec) = OPTIONS.iter().find(|spec| spec.long == name) {
+        return Ok(spec);
+    }
+
+    let candidates: Vec<&OptionSpec> = OPTIONS
+        .iter()
+        .filter(|spec| !name.is_empty() && spec.long.starts_with(name))
+        .collect();
+
+    match candidates.as_slice() {
+        [spec] => Ok(spec),
+        [] => Err(ConfigError::UnknownLongOption(format!("--{}", name))),
+        _ => Err(ConfigError::AmbiguousOption(
+            format!("--{}", name),
+            candidates.iter().map(|spec| spec.long).collect(),
+        )),
+    }
+}
+
+/// APPLY: aplica una opcion a la configuracion. Devuelve un comando si la opcion termina la
+/// lectura de argumentos (como --help)
+fn apply(
+    spec: &OptionSpec,
+    value: Option<String>,
+    config: &mut Config,
+    pattern_given: &mut bool,
+) -> Result<Option<Command>, ConfigError> {
+    let value = value.unwrap_or_default();
+
+    match spec.long {
+        "after-context" => config.after_context = Some(parse_number(spec.long, &value)?),
+        "before-context" => config.before_context = Some(parse_number(spec.long, &value)?),
+        "binary-files" => {
+            config.binary_files = match value.as_str() {
+                "binary" => BinaryFiles::Binary,
+                "text" => BinaryFiles::Text,
+                "without-match" => BinaryFiles::WithoutMatch,
+                _ => return Err(ConfigError::InvalidValue(spec.long, value)),
+            }
+        }
+        "byte-offset" => config.byte_offset = true,
+        "color" => {
+            config.color = match value.as_str() {
+                "always" | "yes" | "force" => ColorChoice::Always,
+                "never" | "no" | "none" => ColorChoice::Never,
+                "auto" | "tty" | "if-tty" => ColorChoice::Auto,
+                _ => return Err(ConfigError::InvalidValue(spec.long, value)),
+            }
+        }
+        "column" => config.column = true,
+        "context" => config.context = Some(parse_number(spec.long, &value)?),
+        "count" => config.count = true,
+        "dereference-recursive" => {
+            config.recursive = true;
+            config.dereference = true;
+        }
+        "exclude" => config.exclude.push(value),
+        "exclude-dir" => config.exclude_dir.push(value),
+        "extended-regexp" => config.fixed_strings = false,
+        "file" => {
+            config.pattern_files.push(value);
+            *pattern_given = true;
+        }
+        "files-with-matches" => {
+            config.files_with_matches = true;
+            config.files_without_match = false;
+        }
+        "files-without-match" => {
+            config.files_without_match = true;
+            config.files_with_matches = false;
+        }
+        "fixed-strings" => config.fixed_strings = true,
+        "group-separator" => {
+            config.group_separator = Some(value);
+            config.no_group_separator = false;
+        }
+        "help" => return Ok(Some(Command::Help)),
+        "hidden" => config.hidden = true,
+        "ignore-case" => config.ignore_case = true,
+        "include" => config.include.push(value),
+        "invert-match" => config.invert_match = true,
+        "format" => config.format = Some(value),
+        "json" => config.json = true,
+        "label" => config.label = Some(value),
+        "line-number" => config.line_number = true,
+        "line-regexp" => config.line_regexp = true,
+        "max-count" => config.max_count = Some(parse_number(spec.long, &value)? as u64),
+        "max-depth" => config.max_depth = Some(parse_number(spec.long, &value)?),
+        "mmap" => config.mmap = Some(true),
+        "multiline" => config.multiline = true,
+        "no-filename" => config.with_filename = Some(false),
+        "no-group-separator" => config.no_group_separator = true,
+        "no-ignore" => config.no_ignore = true,
+        "no-messages" => config.no_messages = true,
+        "no-mmap" => config.mmap = Some(false),
+        "null" => config.null = true,
+        "only-matching" => config.only_matching = true,
+        "quiet" => config.quiet = true,
+        "recursive" => {
+            config.recursive = true;
+            config.dereference = false;
+        }
+        "regexp" => {
+            config.patterns.push(value);
+            *pattern_given = true;
+        }
+        "sort" => {
+            config.sort = match value.as_str() {
+                "none" => SortBy::None,
+                "path" => SortBy::Path,
+                _ => return Err(ConfigError::InvalidValue(spec.long, value)),
+            }
+        }
+        "text" => config.binary_files = BinaryFiles::Text,
+        "threads" => match parse_number(spec.long, &value)? {
+            0 => return Err(ConfigError::InvalidValue(spec.long, value)),
+            threads => config.threads = Some(threads),
+        },
+        "version" => return Ok(Some(Command::Version)),
+        "vimgrep" => config.vimgrep = true,
+        "with-filename" => config.with_filename = Some(true),
+        "word-regexp" => config.word_regexp = true,
+        _ => {}
+    }
+
+    Ok(None)
+}
+
+/// PARSE_NUMBER: lee el valor numerico de una opcion
+fn parse_number(name: &'static str, value: &str) -> Result<usize, ConfigError> {
+    value
+        .parse()
+        .map_err(|_| ConfigError::InvalidValue(name, value.to_string()))
+}
@@ -0,0 +1,194 @@
+use std::path::Path;
+
+/// Patron de nombres de archivo al estilo del shell:
+///
+/// - `*` cualquier texto sin '/'
+/// - `**` cualquier texto, incluso con '/'; "**/" tambien puede no matchear nada
+/// - `?` un caracter que no sea '/'
+/// - `[abc]`, `[a-z]`, `[!abc]` o `[^abc]` un caracter del conjunto (o fuera de el)
+/// - `\c` el caracter c sin interpretar
+///
+/// Un '[' sin su ']' se toma literalmente, como hace fnmatch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Glob {
+    pattern: String,
+    tokens: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Literal(char),
+    Any,
+    Star,
+    DoubleStar,
+    AnyDirs, // "**/"
+    Set {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Glob {
+    pub fn new(pattern: &str) -> Self {
+        Glob {
+            pattern: pattern.to_string(),
+            tokens: parse(pattern),
+        }
+    }
+
+    /// PATTERN: devuelve el patron original
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// IS_MATCH: indica si el patron matchea todo el texto
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        match_tokens(&self.tokens, &text)
+    }
+
+    /// MATCHES_PATH: un patron con '/' se compara con la ruta completa, uno sin '/' solo con el
+    /// nombre del archivo
+    pub fn matches_path(&self, path: &Path) -> bool {
+        if self.pattern.contains('/') {
+            let path = path.to_string_lossy();
+            self.is_match(path.strip_prefix("./").unwrap_or(&path))
+        } else {
+            path.file_name()
+                .map(|name| self.is_match(&name.to_string_lossy()))
+                .unwrap_or(false)
+        }
+    }
+}
+
+/// PARSE: separa el patron en partes
+fn parse(pattern: &str) -> Vec<Token> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                // "***" es lo mismo que "**"
+                while chars.get(i) == Some(&'*') {
+                    i += 1;
+                }
+                if chars.get(i) == Some(&'/') {
+                    tokens.push(Token::AnyDirs);
+                    i += 1;
+                } else {
+                    tokens.push(Token::DoubleStar);
+                }
+            }
+            '*' => {
+                tokens.push(Token::Star);
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::Any);
+                i += 1;
+            }
+            '[' => match parse_set(&chars, i) {
+                Some((token, next)) => {
+                    tokens.push(token);
+                    i = next;
+                }
+                None => {
+                    tokens.push(Token::Literal('['));
+                    i += 1;
+                }
+            },
+            '\\' if i + 1 < chars.len() => {
+                tokens.push(Token::Literal(chars[i + 1]));
+                i += 2;
+            }
+            c => {
+                tokens.push(Token::Literal(c));
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+/// PARSE_SET: lee un conjunto "[...]" que empieza en `start`. Devuelve el conjunto y la posicion
+/// siguiente al ']', o None si no se cierra
+fn parse_set(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut i = start + 1;
+    let negated = matches!(chars.get(i), Some('!') | Some('^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut ranges = vec![];
+    let mut first = true;
+    loop {
+        let c = *chars.get(i)?;
+        if c == ']' && !first {
+            return Some((Token::Set { negated, ranges }, i + 1));
+        }
+        first = false;
+
+        let (from, width) = match c {
+            '\\' => (*chars.get(i + 1)?, 2),
+            _ => (c, 1),
+        };
+        i += width;
+
+        // "a-z" es un rango; un '-' antes del ']' es literal
+        if chars.get(i) == Some(&'-') && chars.get(i + 1).is_some_and(|c| *c != ']') {
+            let to = *chars.get(i + 1)?;
+            ranges.push((from.min(to), from.max(to)));
+            i += 2;
+        } else {
+            ranges.push((from, from));
+        }
+    }
+}
+
+/// MATCH_TOKENS: compara las partes del patron con el texto probando cada largo posible para
+/// los comodines
+fn match_tokens(tokens: &[Token], text: &[char]) -> bool {
+    let Some(token) = tokens.first() else {
+        return text.is_empty();
+    };
+    let rest = &tokens[1..];
+
+    match token {
+        Token::Star => {
+            for i in 0..=text.len() {
+                if match_tokens(rest, &text[i..]) {
+                    return true;
+                }
+                if text.get(i) == Some(&'/') {
+                    return false;
+                }
+            }
+            false
+        }
+        Token::DoubleStar => (0..=text.len()).any(|i| match_tokens(rest, &text[i..])),
+        Token::AnyDirs => {
+            match_tokens(rest, text)
+                || (0..text.len())
+                    .filter(|i| text[*i] == '/')
+                    .any(|i| match_tokens(rest, &text[i + 1..]))
+        }
+        Token::Any => match text.first() {
+            Some(c) if *c != '/' => match_tokens(rest, &text[1..]),
+            _ => false,
+        },
+        Token::Literal(expected) => match text.first() {
+            Some(c) if c == expected => match_tokens(rest, &text[1..]),
+            _ => false,
+        },
+        Token::Set { negated, ranges } => match text.first() {
+            Some(c) if *c != '/' => {
+                let inside = ranges.iter().any(|(from, to)| from <= c && c <= to);
+                inside != *negated && match_tokens(rest, &text[1..])
+            }
+            _ => false,
+        },
+    }
+}
@@ -0,0 +1,202 @@
+use crate::glob::Glob;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Resultado de comparar una ruta con las reglas de ignorado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IgnoreMatch {
+    None,      // Ninguna regla habla de la ruta
+    Ignore,    // La ultima regla que matchea la ignora
+    Whitelist, // La ultima regla que matchea es una negacion ("!patron")
+}
+
+/// Una linea de un archivo .gitignore
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreRule {
+    pub glob: Glob,
+    pub negated: bool, // "!patron": vuelve a incluir lo que ignoro una regla anterior
+    pub dir_only: bool, // "patron/": solo matchea directorios
+    pub anchored: bool, // Con '/' al principio o en el medio: relativo al directorio del archivo
+}
+
+impl IgnoreRule {
+    /// PARSE: lee una linea; las vacias y los comentarios no son reglas
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        // Los espacios finales no cuentan, salvo que esten escapados
+        while line.ends_with(' ') && !line.ends_with("\\ ") {
+            line = &line[..line.len() - 1];
+        }
+
+        // "\!" y "\#" empiezan con el caracter literal
+        let negated = line.starts_with('!');
+        if negated || line.starts_with("\\!") || line.starts_with("\\#") {
+            line = &line[1..];
+        }
+
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(IgnoreRule {
+            glob: Glob::new(line),
+            negated,
+            dir_only,
+            anchored,
+        })
+    }
+
+    /// IS_MATCH: `relative` es la ruta relativa al directorio del archivo de reglas
+    pub fn is_match(&self, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        if self.anchored {
+            self.glob.is_match(relative)
+        } else {
+            let name = relative.rsplit('/').next().unwrap_or(relative);
+            self.glob.is_match(name)
+        }
+    }
+}
+
+/// Las reglas de un archivo de ignorado, que valen para su directorio y los de adentro
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IgnoreFile {
+    pub dir: PathBuf,
+    pub rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreFile {
+    pub fn parse(dir: impl Into<PathBuf>, contents: &str) -> Self {
+        IgnoreFile {
+            dir: dir.into(),
+            rules: contents.lines().filter_map(IgnoreRule::parse).collect(),
+        }
+    }
+
+    /// READ: lee el archivo `file` con reglas para `dir`. Si no existe o no se puede leer no hay
+    /// reglas
+    pub fn read(dir: &Path, file: &Path) -> Option<Self> {
+        let contents = fs::read(file).ok()?;
+        Some(Self::parse(dir, &String::from_utf8_lossy(&contents)))
+    }
+
+    /// MATCHED: la ultima regla que matchea decide
+    pub fn matched(&self, path: &Path, is_dir: bool) -> IgnoreMatch {
+        let Ok(relative) = path.strip_prefix(&self.dir) else {
+            return IgnoreMatch::None;
+        };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+
+        match self
+            .rules
+            .iter()
+            .rev()
+            .find(|rule| rule.is_match(&relative, is_dir))
+        {
+            Some(rule) if rule.negated => IgnoreMatch::Whitelist,
+            Some(_) => IgnoreMatch::Ignore,
+            None => IgnoreMatch::None,
+        }
+    }
+}
+
+/// Las reglas que valen en un directorio: las suyas y las de los directorios que lo contienen.
+///
+/// Precedencia, de mayor a menor: .ignore, .gitignore y .git/info/exclude. Un .ignore de un
+/// directorio de afuera gana sobre un .gitignore de uno de adentro; entre archivos del mismo
+/// tipo, el del directorio mas profundo. Los .gitignore solo cuentan dentro de un repositorio
+/// (un directorio con .git)
+#[derive(Debug, Default)]
+pub struct IgnoreStack {
+    parent: Option<Rc<IgnoreStack>>,
+    ignore: Option<IgnoreFile>,
+    gitignore: Option<IgnoreFile>,
+    exclude: Option<IgnoreFile>, // .git/info/exclude si el directorio es la raiz de un repositorio
+    in_repo: bool,
+}
+
+impl IgnoreStack {
+    /// FOR_ROOT: arma las reglas que valen en `dir` (una ruta absoluta) leyendo tambien las de
+    /// los directorios que lo contienen dentro del mismo repositorio
+    pub fn for_root(dir: &Path) -> Rc<Self> {
+        let repo = dir
+            .ancestors()
+            .find(|ancestor| ancestor.join(".git").exists());
+
+        // Desde la raiz del repositorio hasta el padre de `dir`
+        let mut parents: Vec<&Path> = match repo {
+            Some(repo) => dir
+                .ancestors()
+                .skip(1)
+                .take_while(|a| a.starts_with(repo))
+                .collect(),
+            None => vec![],
+        };
+        parents.reverse();
+
+        let mut stack = Rc::new(IgnoreStack::default());
+        for parent in parents {
+            stack = stack.child(parent);
+        }
+        stack.child(dir)
+    }
+
+    /// CHILD: agrega las reglas del directorio `dir`
+    pub fn child(self: &Rc<Self>, dir: &Path) -> Rc<Self> {
+        let is_repo = dir.join(".git").exists();
+        let in_repo = self.in_repo || is_repo;
+
+        let ignore = IgnoreFile::read(dir, &dir.join(".ignore"));
+        let gitignore = match in_repo {
+            true => IgnoreFile::read(dir, &dir.join(".gitignore")),
+            false => None,
+        };
+        let exclude = match is_repo {
+            true => IgnoreFile::read(dir, &dir.join(".git").join("info").join("exclude")),
+            false => None,
+        };
+
+        Rc::new(IgnoreStack {
+            parent: Some(Rc::clone(self)),
+            ignore,
+            gitignore,
+            exclude,
+            in_repo,
+        })
+    }
+
+    /// MATCHED: busca la primera regla que hable de la ruta, primero en todos los .ignore desde
+    /// el directorio mas profundo, despues en los .gitignore y por ultimo en los exclude
+    pub fn matched(&self, path: &Path, is_dir: bool) -> IgnoreMatch {
+        let ignores = self.chain().flat_map(|stack| stack.ignore.iter());
+        let gitignores = self.chain().flat_map(|stack| stack.gitignore.iter());
+        let excludes = self.chain().flat_map(|stack| stack.exclude.iter());
+
+        ignores
+            .chain(gitignores)
+            .chain(excludes)
+            .map(|file| file.matched(path, is_dir))
+            .find(|matched| *matched != IgnoreMatch::None)
+            .unwrap_or(IgnoreMatch::None)
+    }
+
+    /// IS_IGNORED: indica si la ruta se saltea
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        self.matched(path, is_dir) == IgnoreMatch::Ignore
+    }
+
+    fn chain(&self) -> impl Iterator<Item = &IgnoreStack> {
+        std::iter::successors(Some(self), |stack| stack.parent.as_deref())
+    }
+}
@@ -0,0 +1,280 @@
+use crate::config::{BinaryFiles, Config};
+use crate::matcher::Matcher;
+use crate::printer::shown_matches;
+use crate::regex_match::Match;
+use crate::searcher::{Sink, SinkContext, SinkMatch};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+/// Estadisticas de la busqueda en un archivo, o de toda la busqueda si se suman
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub elapsed: Duration,
+    pub searches: u64,
+    pub searches_with_match: u64,
+    pub bytes_printed: u64,
+    pub matched_lines: u64,
+    pub matches: u64,
+}
+
+impl Stats {
+    /// ADD: suma las estadisticas de otra busqueda
+    pub fn add(&mut self, other: &Stats) {
+        self.elapsed += other.elapsed;
+        self.searches += other.searches;
+        self.searches_with_match += other.searches_with_match;
+        self.bytes_printed += other.bytes_printed;
+        self.matched_lines += other.matched_lines;
+        self.matches += other.matches;
+    }
+}
+
+/// Escribe los resultados de un archivo como JSON Lines, un objeto por evento:
+///
+/// - `begin` antes del primer resultado del archivo
+/// - `match` con las lineas, su numero, su posicion en el archivo y cada match dentro de ellas
+/// - `context` con el mismo formato, para las lineas de contexto
+/// - `end` con las estadisticas del archivo, si hubo un `begin`
+///
+/// El texto que no es UTF-8 valido se escribe en base64 como `{"bytes":...}` en lugar de
+/// `{"text":...}`. El mensaje `summary` de toda la busqueda lo escribe `write_summary`
+pub struct JsonPrinter<'a, W: Write> {
+    config: &'a Config,
+    matcher: &'a dyn Matcher,
+    name: &'a str,
+    out: W,
+    started: Instant,
+    begun: bool, // Si ya se escribio el mensaje `begin`
+    stats: Stats,
+}
+
+impl<'a, W: Write> JsonPrinter<'a, W> {
+    pub fn new(config: &'a Config, matcher: &'a dyn Matcher, name: &'a str, out: W) -> Self {
+        JsonPrinter {
+            config,
+            matcher,
+            name,
+            out,
+            started: Instant::now(),
+            begun: false,
+            stats: Stats {
+                searches: 1,
+                ..Stats::default()
+            },
+        }
+    }
+
+    /// STATS: estadisticas del archivo hasta ahora
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// FINISH: escribe el mensaje `end` si el archivo tuvo resultados
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.stats.elapsed = self.started.elapsed();
+        if !self.begun {
+            return Ok(());
+        }
+
+        let mut message = vec![];
+        message.extend_from_slice(b"{\"type\":\"end\",\"data\":{\"path\":");
+        write_data(&mut message, self.name.as_bytes());
+        message.extend_from_slice(b",\"stats\":");
+        // Los bytes de este mensaje no llegan a contarse
+        write_stats(&mut message, &self.stats);
+        message.extend_from_slice(b"}}\n");
+        self.emit(&message)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// BEGIN: escribe el mensaje `begin` antes del primer resultado
+    fn begin(&mut self) -> io::Result<()> {
+        if self.begun {
+            return Ok(());
+        }
+        self.begun = true;
+
+        let mut message = vec![];
+        message.extend_from_slice(b"{\"type\":\"begin\",\"data\":{\"path\":");
+        write_data(&mut message, self.name.as_bytes());
+        message.extend_from_slice(b"}}\n");
+        self.emit(&message)
+    }
+
+    /// WRITE_LINES: escribe un mensaje `match` o `context` con las lineas y sus matches
+    fn write_lines(
+        &mut self,
+        kind: &str,
+        bytes: &[u8],
+        absolute_offset: u64,
+        line_number: Option<u64>,
+        submatches: &[Match],
+    ) -> io::Result<()> {
+        self.begin()?;
+
+        let mut message = vec![];
+        write!(message, "{{\"type\":\"{}\",\"data\":{{\"path\":", kind)?;
+        write_data(&mut message, self.name.as_bytes());
+        message.extend_from_slice(b",\"lines\":");
+        write_data(&mut message, bytes);
+        match line_number {
+            Some(line_number) => write!(message, ",\"line_number\":{}", line_number)?,
+            None => message.extend_from_slice(b",\"line_number\":null"),
+        }
+        write!(message, ",\"absolute_offset\":{}", absolute_offset)?;
+        message.extend_from_slice(b",\"submatches\":[");
+        for (i, m) in submatches.iter().enumerate() {
+            if i > 0 {
+                message.push(b',');
+            }
+            message.extend_from_slice(b"{\"match\":");
+            write_data(&mut message, &bytes[m.start..m.end]);
+            write!(message, ",\"start\":{},\"end\":{}}}", m.start, m.end)?;
+        }
+        message.extend_from_slice(b"]}}\n");
+        self.emit(&message)
+    }
+
+    /// EMIT: escribe un mensaje completo y lo cuenta en las estadisticas
+    fn emit(&mut self, message: &[u8]) -> io::Result<()> {
+        self.stats.bytes_printed += message.len() as u64;
+        self.out.write_all(message)
+    }
+}
+
+impl<W: Write> Sink for JsonPrinter<'_, W> {
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool> {
+        let submatches = shown_matches(self.matcher, found.bytes, self.config.invert_match, true);
+
+        self.stats.searches_with_match = 1;
+        self.stats.matched_lines += 1;
+        self.stats.matches += submatches.len() as u64;
+        let (offset, line_number) = (found.absolute_offset, found.line_number);
+        self.write_lines("match", found.bytes, offset, line_number, &submatches)?;
+
+        Ok(self
+            .config
+            .max_count
+            .is_none_or(|max| self.stats.matched_lines < max))
+    }
+
+    fn context(&mut self, context: &SinkContext) -> io::Result<bool> {
+        let invert_match = self.config.invert_match;
+        let submatches = shown_matches(self.matcher, context.bytes, invert_match, false);
+        let (offset, line_number) = (context.absolute_offset, context.line_number);
+        self.write_lines("context", context.bytes, offset, line_number, &submatches)?;
+        Ok(true)
+    }
+
+    /// Las lineas de un archivo binario se escriben igual, en base64 si hace falta
+    fn binary_data(&mut self, _offset: u64) -> io::Result<bool> {
+        Ok(self.config.binary_files != BinaryFiles::WithoutMatch)
+    }
+}
+
+/// WRITE_SUMMARY: escribe el mensaje `summary` con las estadisticas de toda la busqueda, que
+/// tardo `elapsed` en total
+pub fn write_summary<W: Write>(out: &mut W, stats: &Stats, elapsed: Duration) -> io::Result<()> {
+    let mut message = vec![];
+    message.extend_from_slice(b"{\"type\":\"summary\",\"data\":{\"elapsed_total\":");
+    write_duration(&mut message, elapsed);
+    message.extend_from_slice(b",\"stats\":");
+    write_stats(&mut message, stats);
+    message.extend_from_slice(b"}}\n");
+    out.write_all(&message)
+}
+
+/// WRITE_STATS: las estadisticas como objeto JSON
+fn write_stats(out: &mut Vec<u8>, stats: &Stats) {
+    out.extend_from_slice(b"{\"elapsed\":");
+    write_duration(out, stats.elapsed);
+    let fields = [
+        ("searches", stats.searches),
+        ("searches_with_match", stats.searches_with_match),
+        ("bytes_printed", stats.bytes_printed),
+        ("matched_lines", stats.matched_lines),
+        ("matches", stats.matches),
+    ];
+    for (name, value) in fields {
+        out.extend_from_slice(format!(",\"{}\":{}", name, value).as_bytes());
+    }
+    out.push(b'}');
+}
+
+/// WRITE_DURATION: una duracion en segundos y nanosegundos, y en texto para leerla
+fn write_duration(out: &mut Vec<u8>, duration: Duration) {
+    let text = format!(
+        "{{\"secs\":{},\"nanos\":{},\"human\":\"{:.6}s\"}}",
+        duration.as_secs(),
+        duration.subsec_nanos(),
+        duration.as_secs_f64()
+    );
+    out.extend_from_slice(text.as_bytes());
+}
+
+/// WRITE_DATA: escribe `{"text":...}` si los bytes son UTF-8 valido, si no `{"bytes":...}` en
+/// base64
+pub fn write_data(out: &mut Vec<u8>, bytes: &[u8]) {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => {
+            out.extend_from_slice(b"{\"text\":");
+            write_string(out, text);
+        }
+        Err(_) => {
+            out.extend_from_slice(b"{\"bytes\":\"");
+            out.extend_from_slice(base64(bytes).as_bytes());
+            out.push(b'"');
+        }
+    }
+    out.push(b'}');
+}
+
+/// WRITE_STRING: escribe un texto como string JSON, escapando las comillas, las barras y los
+/// caracteres de control
+pub fn write_string(out: &mut Vec<u8>, text: &str) {
+    out.push(b'"');
+    for c in text.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            '\n' => out.extend_from_slice(b"\\n"),
+            '\r' => out.extend_from_slice(b"\\r"),
+            '\t' => out.extend_from_slice(b"\\t"),
+            c if (c as u32) < 0x20 => {
+                out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes())
+            }
+            c => {
+                let mut buffer = [0; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            }
+        }
+    }
+    out.push(b'"');
+}
+
+/// BASE64: codifica los bytes en base64 estandar, con relleno
+pub fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = match *chunk {
+            [a, b, c] => (a as u32) << 16 | (b as u32) << 8 | c as u32,
+            [a, b] => (a as u32) << 16 | (b as u32) << 8,
+            [a] => (a as u32) << 16,
+            _ => unreachable!(),
+        };
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (group >> (18 - 6 * i)) & 0x3F;
+                encoded.push(ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -1,7 +1,27 @@
-pub mod evaluated_step;
+pub mod color;
+pub mod config;
+pub mod glob;
+pub mod ignore;
+pub mod json;
+pub mod line_reader;
+pub mod literal_matcher;
+pub mod matcher;
+pub mod mmap;
+pub mod output;
+pub mod pool;
+pub mod printer;
 pub mod regex;
+pub mod regex_ast;
 pub mod regex_class;
+pub mod regex_error;
+pub mod regex_hir;
+pub mod regex_match;
+pub mod regex_pattern;
+pub mod regex_program;
 pub mod regex_rep;
 pub mod regex_step;
 pub mod regex_val;
+pub mod searcher;
+pub mod template;
 pub mod utils;
+pub mod walk;
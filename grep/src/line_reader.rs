@@ -0,0 +1,118 @@
+use crate::utils::{find_byte, rfind_byte};
+use std::io::{self, Read};
+
+/// Tamaño inicial del buffer de lectura
+pub const DEFAULT_CAPACITY: usize = 64 * 1024;
+
+/// Lector de lineas sobre un buffer de tamaño fijo que se reutiliza en cada lectura. Las lineas
+/// se devuelven como porciones del buffer, sin copiarlas ni convertirlas a `String`.
+///
+/// Los finales de linea se buscan solo en los bytes recien leidos, asi cada byte se recorre
+/// una vez. Si una linea no entra en el buffer, el buffer se agranda hasta que entre
+pub struct LineReader<R> {
+    source: R,
+    buffer: Vec<u8>,
+    start: usize,    // Principio de lo que todavia no se consumio
+    end: usize,      // Fin de lo leido
+    complete: usize, // Fin de la ultima linea completa que hay en el buffer
+    offset: u64,     // Posicion en la fuente de buffer[start]
+    eof: bool,
+}
+
+impl<R: Read> LineReader<R> {
+    pub fn new(source: R) -> Self {
+        Self::with_capacity(source, DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(source: R, capacity: usize) -> Self {
+        LineReader {
+            source,
+            buffer: vec![0; capacity.max(1)],
+            start: 0,
+            end: 0,
+            complete: 0,
+            offset: 0,
+            eof: false,
+        }
+    }
+
+    /// FILL: devuelve todas las lineas completas que hay en el buffer, leyendo mas si no hay
+    /// ninguna. Al final de la fuente devuelve lo que quede aunque no termine en '\n', y cuando
+    /// ya no queda nada, una porcion vacia
+    pub fn fill(&mut self) -> io::Result<&[u8]> {
+        let end = self.fill_lines()?;
+        Ok(&self.buffer[self.start..end])
+    }
+
+    /// CONSUME: marca como leidos los primeros `amount` bytes de lo que devolvio `fill`
+    pub fn consume(&mut self, amount: usize) {
+        let amount = amount.min(self.end - self.start);
+        self.start += amount;
+        self.offset += amount as u64;
+    }
+
+    /// NEXT_LINE: devuelve la proxima linea, con su '\n' si lo tiene
+    pub fn next_line(&mut self) -> io::Result<Option<&[u8]>> {
+        let end = self.fill_lines()?;
+        let available = &self.buffer[self.start..end];
+        if available.is_empty() {
+            return Ok(None);
+        }
+
+        let len = find_byte(b'\n', available).map_or(available.len(), |i| i + 1);
+        let start = self.start;
+        self.consume(len);
+        Ok(Some(&self.buffer[start..start + len]))
+    }
+
+    /// OFFSET: posicion en la fuente del proximo byte sin consumir
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// CAPACITY: tamaño actual del buffer
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// FILL_LINES: lee hasta tener al menos una linea completa (o el final de la fuente) y
+    /// devuelve hasta donde llegan las lineas disponibles
+    fn fill_lines(&mut self) -> io::Result<usize> {
+        loop {
+            if self.complete > self.start {
+                return Ok(self.complete);
+            }
+            if self.eof {
+                return Ok(self.end);
+            }
+
+            self.make_room();
+            let read = match self.source.read(&mut self.buffer[self.end..]) {
+                Ok(read) => read,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            };
+
+            if read == 0 {
+                self.eof = true;
+            } else if let Some(i) = rfind_byte(b'\n', &self.buffer[self.end..self.end + read]) {
+                self.complete = self.end + i + 1;
+            }
+            self.end += read;
+        }
+    }
+
+    /// MAKE_ROOM: mueve lo que falta consumir al principio del buffer, y si el buffer esta
+    /// lleno con una sola linea incompleta, lo agranda al doble
+    fn make_room(&mut self) {
+        if self.start > 0 {
+            self.buffer.copy_within(self.start..self.end, 0);
+            self.end -= self.start;
+            self.complete = self.complete.saturating_sub(self.start);
+            self.start = 0;
+        }
+        if self.end == self.buffer.len() {
+            self.buffer.resize(self.buffer.len() * 2, 0);
+        }
+    }
+}
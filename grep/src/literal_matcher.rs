@@ -0,0 +1,225 @@
+use crate::matcher::Matcher;
+use crate::regex_match::Match;
+use crate::regex_program::{MatchBoundary, MatchSemantics};
+use crate::utils::decode_char;
+use std::collections::VecDeque;
+
+const ROOT: usize = 0;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    edges: Vec<(u8, usize)>, // Ordenadas por byte
+    fail: usize,             // Nodo del sufijo propio mas largo que esta en el trie
+    output: Option<usize>,   // Patron que termina en este nodo
+    dict: Option<usize>,     // Proximo nodo con patron siguiendo los `fail`
+}
+
+impl Node {
+    fn edge(&self, byte: u8) -> Option<usize> {
+        self.edges
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()
+            .map(|i| self.edges[i].1)
+    }
+}
+
+/// Buscador de una o varias cadenas fijas (sin metacaracteres) basado en un automata de
+/// Aho-Corasick: recorre el texto una sola vez sin importar cuantos patrones haya
+#[derive(Debug, Clone)]
+pub struct LiteralMatcher {
+    nodes: Vec<Node>,
+    lengths: Vec<usize>,
+    max_len: usize,
+    empty: Option<usize>,
+    semantics: MatchSemantics,
+    boundary: MatchBoundary,
+}
+
+impl LiteralMatcher {
+    pub fn new<P: AsRef<[u8]>>(patterns: &[P]) -> Self {
+        let mut matcher = LiteralMatcher {
+            nodes: vec![Node::default()],
+            lengths: vec![],
+            max_len: 0,
+            empty: None,
+            semantics: MatchSemantics::default(),
+            boundary: MatchBoundary::default(),
+        };
+
+        for (index, pattern) in patterns.iter().enumerate() {
+            matcher.insert(index, pattern.as_ref());
+        }
+        matcher.build_links();
+
+        matcher
+    }
+
+    /// WITH_SEMANTICS: cambia la semantica con la que se elige el match (por defecto leftmost-first)
+    pub fn with_semantics(mut self, semantics: MatchSemantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
+
+    pub fn semantics(&self) -> MatchSemantics {
+        self.semantics
+    }
+
+    /// WITH_BOUNDARY: exige que los matches sean palabras o lineas enteras
+    pub fn with_boundary(mut self, boundary: MatchBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
+
+    pub fn boundary(&self) -> MatchBoundary {
+        self.boundary
+    }
+
+    /// PATTERNS_LEN: cantidad de patrones buscados
+    pub fn patterns_len(&self) -> usize {
+        self.lengths.len()
+    }
+
+    fn insert(&mut self, index: usize, pattern: &[u8]) {
+        self.lengths.push(pattern.len());
+        self.max_len = self.max_len.max(pattern.len());
+
+        if pattern.is_empty() {
+            self.empty.get_or_insert(index);
+            return;
+        }
+
+        let mut node = ROOT;
+        for &byte in pattern {
+            node = match self.nodes[node].edge(byte) {
+                Some(next) => next,
+                None => {
+                    let next = self.nodes.len();
+                    self.nodes.push(Node::default());
+                    let edges = &mut self.nodes[node].edges;
+                    let position = edges.partition_point(|(b, _)| *b < byte);
+                    edges.insert(position, (byte, next));
+                    next
+                }
+            };
+        }
+
+        // Si el patron esta repetido se queda el primero
+        self.nodes[node].output.get_or_insert(index);
+    }
+
+    /// BUILD_LINKS: calcula los enlaces de falla recorriendo el trie por niveles
+    fn build_links(&mut self) {
+        let mut queue: VecDeque<usize> = self.nodes[ROOT].edges.iter().map(|e| e.1).collect();
+
+        while let Some(node) = queue.pop_front() {
+            for i in 0..self.nodes[node].edges.len() {
+                let (byte, child) = self.nodes[node].edges[i];
+
+                let mut fail = self.nodes[node].fail;
+                let child_fail = loop {
+                    if let Some(next) = self.nodes[fail].edge(byte) {
+                        break next;
+                    }
+                    if fail == ROOT {
+                        break ROOT;
+                    }
+                    fail = self.nodes[fail].fail;
+                };
+
+                self.nodes[child].fail = child_fail;
+                self.nodes[child].dict = match self.nodes[child_fail].output {
+                    Some(_) => Some(child_fail),
+                    None => self.nodes[child_fail].dict,
+                };
+                queue.push_back(child);
+            }
+        }
+    }
+
+    fn next_state(&self, mut node: usize, byte: u8) -> usize {
+        loop {
+            if let Some(next) = self.nodes[node].edge(byte) {
+                return next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// ACCEPTS: indica si un match cumple con los bordes pedidos
+    fn accepts(&self, haystack: &[u8], found: Match) -> bool {
+        self.boundary.is_start(haystack, found.start) && self.boundary.is_end(haystack, found.end)
+    }
+
+    /// FIRST_EMPTY: el primer lugar desde `start` donde un patron vacio cumple con los bordes
+    fn first_empty(&self, haystack: &[u8], start: usize) -> Option<usize> {
+        let mut at = start;
+        loop {
+            if self.accepts(haystack, Match::new(at, at)) {
+                return Some(at);
+            }
+            at += decode_char(&haystack[at..])?.1;
+        }
+    }
+
+    /// IS_BETTER: gana el match que empieza antes; si empiezan igual, decide la semantica
+    fn is_better(&self, candidate: (Match, usize), best: Option<(Match, usize)>) -> bool {
+        let (found, index) = candidate;
+        match best {
+            None => true,
+            Some((best, best_index)) => {
+                found.start < best.start
+                    || (found.start == best.start
+                        && match self.semantics {
+                            MatchSemantics::LeftmostFirst => index < best_index,
+                            MatchSemantics::LeftmostLongest => found.end > best.end,
+                        })
+            }
+        }
+    }
+}
+
+impl Matcher for LiteralMatcher {
+    fn find_at(&self, haystack: &[u8], start: usize) -> Option<Match> {
+        if start > haystack.len() {
+            return None;
+        }
+
+        let mut best: Option<(Match, usize)> = self.empty.and_then(|i| {
+            let at = self.first_empty(haystack, start)?;
+            Some((Match::new(at, at), i))
+        });
+        let mut node = ROOT;
+
+        for (i, &byte) in haystack.iter().enumerate().skip(start) {
+            let end = i + 1;
+
+            // Ningun match que termine de aca en adelante puede empezar antes que el mejor
+            if let Some((found, _)) = best {
+                if end > found.start + self.max_len {
+                    break;
+                }
+            }
+
+            node = self.next_state(node, byte);
+
+            let mut output = match self.nodes[node].output {
+                Some(_) => Some(node),
+                None => self.nodes[node].dict,
+            };
+            while let Some(current) = output {
+                if let Some(index) = self.nodes[current].output {
+                    let found = Match::new(end - self.lengths[index], end);
+                    if self.accepts(haystack, found) && self.is_better((found, index), best) {
+                        best = Some((found, index));
+                    }
+                }
+                output = self.nodes[current].dict;
+            }
+        }
+
+        best.map(|(found, _)| found)
+    }
+}
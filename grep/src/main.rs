@@ -1,28 +1,434 @@
 use grep::{
-    regex::Regex,
-    utils::{read_args, read_lines},
+    color::{stdout_is_terminal, Colors},
+    config::{parse_args, BinaryFiles, ColorChoice, Command, Config, SortBy, HELP, USAGE},
+    glob::Glob,
+    json::{write_summary, JsonPrinter, Stats},
+    literal_matcher::LiteralMatcher,
+    matcher::Matcher,
+    mmap::MmapChoice,
+    output::{SharedOutput, DEFAULT_CHUNK_SIZE},
+    pool::WorkStealingPool,
+    printer::Printer,
+    regex::{escape, MatchBoundary, MatchSemantics, Regex},
+    searcher::{Searcher, Sink},
+    template::Template,
+    utils::{read_lines, read_lines_from},
+    walk::{Walk, WalkOptions},
 };
+use std::env;
 use std::error::Error;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let args = read_args()?;
+/// Estados de salida de POSIX: se selecciono alguna linea, ninguna, o hubo un error
+const EXIT_SELECTED: i32 = 0;
+const EXIT_NOT_SELECTED: i32 = 1;
+const EXIT_ERROR: i32 = 2;
 
-    let expression = &args[1];
-    let filepath = &args[2];
+fn main() {
+    let config = match parse_args(env::args().skip(1)) {
+        Ok(Command::Search(config)) => *config,
+        Ok(Command::Help) => {
+            print!("{}", HELP);
+            return;
+        }
+        Ok(Command::Version) => {
+            println!("grep {}", env!("CARGO_PKG_VERSION"));
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}: {}", program_name(), err);
+            eprintln!("{}", USAGE);
+            eprintln!("Try '{} --help' for more information.", program_name());
+            process::exit(EXIT_ERROR);
+        }
+    };
+
+    // Los errores se muestran con Display, que incluye la marca bajo la parte invalida del patron
+    match run(&config) {
+        Ok(status) => process::exit(status),
+        Err(err) => {
+            eprintln!("{}: {}", program_name(), err);
+            process::exit(EXIT_ERROR);
+        }
+    }
+}
+
+/// PROGRAM_NAME: nombre con el que se invoco el programa, sin el directorio, para los mensajes
+/// de error
+fn program_name() -> &'static str {
+    static NAME: OnceLock<String> = OnceLock::new();
+    NAME.get_or_init(|| {
+        env::args_os()
+            .next()
+            .and_then(|arg| {
+                let name = Path::new(&arg).file_name()?;
+                Some(name.to_string_lossy().into_owned())
+            })
+            .unwrap_or_else(|| "grep".to_string())
+    })
+}
+
+/// ERROR_MESSAGE: el mensaje de un error sin el " (os error N)" que agrega `io::Error`, como los
+/// de GNU grep: "No such file or directory"
+fn error_message(err: &dyn Display) -> String {
+    let message = err.to_string();
+    let code = message
+        .strip_suffix(')')
+        .and_then(|rest| rest.rsplit_once(" (os error "))
+        .filter(|(_, code)| !code.is_empty() && code.bytes().all(|b| b.is_ascii_digit()));
+    match code {
+        Some((text, _)) => text.to_string(),
+        None => message,
+    }
+}
+
+/// Errores de los archivos que no se pueden leer o recorrer. No cortan la busqueda, pero se
+/// recuerdan para el estado de salida. Con -s no se informan
+struct FileErrors {
+    silent: bool,
+    found: AtomicBool,
+}
+
+impl FileErrors {
+    fn new(silent: bool) -> Self {
+        FileErrors {
+            silent,
+            found: AtomicBool::new(false),
+        }
+    }
+
+    /// REPORT: informa el error de un archivo
+    fn report(&self, name: &str, err: &dyn Display) {
+        self.found.store(true, Ordering::Relaxed);
+        if !self.silent {
+            eprintln!("{}: {}: {}", program_name(), name, error_message(err));
+        }
+    }
+
+    /// FOUND: indica si hubo algun error
+    fn found(&self) -> bool {
+        self.found.load(Ordering::Relaxed)
+    }
+}
+
+/// RUN: hace la busqueda y devuelve el estado de salida. Los errores que impiden buscar (un
+/// patron invalido, por ejemplo) se devuelven como `Err`
+fn run(config: &Config) -> Result<i32, Box<dyn Error>> {
+    let started = Instant::now();
+    let matcher = build_matcher(config)?;
+    let template = match &config.format {
+        Some(format) => Some(Template::parse(format, matcher.as_ref())?),
+        None => None,
+    };
+    let errors = FileErrors::new(config.no_messages);
+    let files = collect_files(config, &errors);
+    let colors = use_color(config.color)
+        .then(|| Colors::parse(&env::var("GREP_COLORS").unwrap_or_default()));
+
+    // Cada archivo se busca en un hilo y escribe su salida por partes de lineas enteras. Con un
+    // solo hilo cada linea se escribe apenas se encuentra, asi `tail -f | grep` funciona
+    let pool = match config.threads {
+        Some(threads) => WorkStealingPool::new(threads),
+        None => WorkStealingPool::default(),
+    };
+    let chunk_size = match pool.threads().min(files.len()) {
+        0 | 1 => 0,
+        _ => DEFAULT_CHUNK_SIZE,
+    };
+    // En una terminal se escribe cada linea; si no, se juntan para hacer menos escrituras
+    let stdout: Box<dyn Write + Send> = match stdout_is_terminal() {
+        true => Box::new(io::stdout()),
+        false => Box::new(BufWriter::new(io::stdout())),
+    };
+    let output = SharedOutput::new(stdout, config.sort == SortBy::Path)
+        .separator(group_separator(config, colors.as_ref())?)
+        .chunk_size(chunk_size);
+    let count = files.len();
+    let stats = Mutex::new(Stats::default());
+    let selected = AtomicBool::new(false);
+    pool.run(files, |index, path| {
+        let mut out = output.file(index);
+        let matcher = matcher.as_ref();
+        let result = match config.json {
+            true => search_json(config, matcher, &path, count, &mut out).map(|found| {
+                let mut stats = stats
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                stats.add(&found);
+                found.matched_lines
+            }),
+            false => {
+                let printer = Printer::new(config, matcher, config.display_name(&path), &mut out)
+                    .colors(colors.as_ref())
+                    .template(template.as_ref());
+                search_path(config, matcher, printer, &path, count)
+            }
+        };
+        // Si fallo la escritura, el error del archivo viene de ahi y no hay que informarlo
+        if let Err(err) = out.finish() {
+            write_error(&err);
+        }
+        // Un archivo que no se puede leer no corta la busqueda en los demas
+        match result {
+            // Con -q el primer match ya decide el resultado, aunque haya habido errores
+            Ok(matches) if config.quiet && matches > 0 => process::exit(EXIT_SELECTED),
+            Ok(matches) if matches > 0 => selected.store(true, Ordering::Relaxed),
+            Ok(_) => {}
+            Err(err) => errors.report(config.display_name(&path), &err),
+        }
+    });
+
+    let mut stdout = output.into_inner();
+    if config.json {
+        let stats = stats
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Err(err) = write_summary(&mut stdout, &stats, started.elapsed()) {
+            write_error(&err);
+        }
+    }
+    if let Err(err) = stdout.flush() {
+        write_error(&err);
+    }
+
+    Ok(match (errors.found(), selected.into_inner()) {
+        (true, _) => EXIT_ERROR,
+        (false, true) => EXIT_SELECTED,
+        (false, false) => EXIT_NOT_SELECTED,
+    })
+}
+
+/// WRITE_ERROR: termina el programa porque no se pudo escribir la salida. Si la cerro el
+/// programa que la lee (`grep ... | head`) no es un error: se termina sin mensaje, como GNU grep
+fn write_error(err: &io::Error) -> ! {
+    if err.kind() == io::ErrorKind::BrokenPipe {
+        process::exit(EXIT_SELECTED);
+    }
+    eprintln!("{}: write error: {}", program_name(), error_message(err));
+    process::exit(EXIT_ERROR);
+}
 
-    let lines = read_lines(filepath.to_string())?;
+/// COLLECT_FILES: arma la lista de archivos a buscar, recorriendo los directorios si la busqueda
+/// es recursiva. Los errores del recorrido se informan a medida que aparecen
+fn collect_files(config: &Config, errors: &FileErrors) -> Vec<String> {
+    let options = walk_options(config);
 
-    for value in lines {
-        let regex = Regex::new(expression);
-        match regex?.test(&value) {
-            Ok(result) => {
-                if result {
-                    println!("{}", &value)
-                }
+    // Sin archivos se lee la entrada estandar, o el directorio actual si la busqueda es recursiva
+    let default_path = [match config.recursive {
+        true => ".".to_string(),
+        false => "-".to_string(),
+    }];
+    let paths = match config.paths.is_empty() {
+        true => &default_path[..],
+        false => &config.paths[..],
+    };
+
+    // El "./" del directorio implicito no se muestra, como en GNU grep
+    let shown = |path: &Path| {
+        let path = path.to_string_lossy().into_owned();
+        match (config.paths.is_empty(), path.strip_prefix("./")) {
+            (true, Some(rest)) => rest.to_string(),
+            _ => path,
+        }
+    };
+
+    let mut files = vec![];
+    for path in paths {
+        if path == "-" || !config.recursive {
+            if path == "-" || options.selects_file(Path::new(path)) {
+                files.push(path.clone());
+            }
+            continue;
+        }
+
+        for entry in Walk::new(path, &options) {
+            match entry {
+                Ok(file) => files.push(shown(&file)),
+                Err(err) => errors.report(&shown(&err.path), &err),
             }
-            Err(err) => println!("Error {}", err),
         }
     }
 
+    files
+}
+
+/// WALK_OPTIONS: arma las opciones del recorrido a partir de la configuracion
+fn walk_options(config: &Config) -> WalkOptions {
+    let globs = |patterns: &[String]| patterns.iter().map(|p| Glob::new(p)).collect();
+    WalkOptions {
+        follow_links: config.dereference,
+        max_depth: config.max_depth,
+        include: globs(&config.include),
+        exclude: globs(&config.exclude),
+        exclude_dir: globs(&config.exclude_dir),
+        ignore_files: !config.no_ignore,
+        hidden: config.hidden,
+    }
+}
+
+/// GROUP_SEPARATOR: la linea que separa los resultados de dos archivos cuando hay contexto
+fn group_separator(config: &Config, colors: Option<&Colors>) -> io::Result<Option<Vec<u8>>> {
+    let Some(separator) = config.group_separator() else {
+        return Ok(None);
+    };
+    let mut line = vec![];
+    match colors {
+        Some(colors) => colors.paint(&mut line, &colors.separator, separator.as_bytes())?,
+        None => line.extend_from_slice(separator.as_bytes()),
+    }
+    line.push(b'\n');
+    Ok(Some(line))
+}
+
+/// USE_COLOR: decide si la salida lleva colores. En modo automatico, solo si es una terminal
+/// que los entiende
+fn use_color(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            stdout_is_terminal() && env::var("TERM").is_ok_and(|term| term != "dumb")
+        }
+    }
+}
+
+/// SEARCH_PATH: busca en un archivo ("-" es la entrada estandar) de una busqueda sobre `count`
+/// archivos, escribe los resultados con `printer` y devuelve cuantas lineas se seleccionaron
+fn search_path<W: Write>(
+    config: &Config,
+    matcher: &dyn Matcher,
+    mut printer: Printer<W>,
+    path: &str,
+    count: usize,
+) -> Result<u64, io::Error> {
+    search_with(config, matcher, path, count, &mut printer)?;
+    printer.finish()?;
+    Ok(printer.matches())
+}
+
+/// SEARCH_JSON: como SEARCH_PATH, pero escribe los resultados como JSON Lines y devuelve las
+/// estadisticas del archivo
+fn search_json<W: Write>(
+    config: &Config,
+    matcher: &dyn Matcher,
+    path: &str,
+    count: usize,
+    out: W,
+) -> Result<Stats, io::Error> {
+    let mut printer = JsonPrinter::new(config, matcher, config.display_name(path), out);
+    search_with(config, matcher, path, count, &mut printer)?;
+    printer.finish()?;
+    Ok(*printer.stats())
+}
+
+/// SEARCH_WITH: busca en un archivo y entrega los resultados a `sink`
+fn search_with<S: Sink>(
+    config: &Config,
+    matcher: &dyn Matcher,
+    path: &str,
+    count: usize,
+    sink: &mut S,
+) -> Result<(), io::Error> {
+    // Con -m 0 no se lee nada, pero -c igual muestra la cuenta
+    if config.max_count == Some(0) {
+        return Ok(());
+    }
+
+    // Los mensajes JSON y las plantillas siempre tienen el numero de linea
+    let line_number =
+        config.line_number || config.vimgrep || config.json || config.format.is_some();
+    let searcher = Searcher::new()
+        .multi_line(config.multiline)
+        .invert_match(config.invert_match)
+        .line_number(line_number)
+        .binary_detection(config.binary_files != BinaryFiles::Text)
+        .before_context(config.before_context())
+        .after_context(config.after_context());
+
+    if path == "-" {
+        searcher.search_reader(matcher, io::stdin().lock(), sink)?;
+    } else {
+        let file = File::open(path)?;
+        let choice = match config.mmap {
+            Some(true) => MmapChoice::Always,
+            Some(false) => MmapChoice::Never,
+            None => MmapChoice::Auto,
+        };
+        let memory_map = choice.should_map(&file.metadata()?, count);
+        searcher
+            .memory_map(memory_map)
+            .search_file(matcher, &file, sink)?;
+    }
     Ok(())
-}
\ No newline at end of file
+}
+
+/// READ_PATTERN_FILE: lee los patrones de -f, uno por linea; "-" es la entrada estandar
+fn read_pattern_file(file: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let lines = match file {
+        "-" => read_lines_from(io::stdin().lock()),
+        _ => read_lines(file.to_string()),
+    };
+    lines.map_err(|err| format!("{}: {}", file, err).into())
+}
+
+/// BUILD_MATCHER: junta los patrones de -e y de -f en un solo buscador que encuentra una
+/// linea si matchea cualquiera de ellos. El buscador es uno solo y lo comparten todos los hilos
+fn build_matcher(config: &Config) -> Result<Box<dyn Matcher + Sync>, Box<dyn Error>> {
+    // Cada patron puede traer varios separados por saltos de linea
+    let mut patterns: Vec<String> = config
+        .patterns
+        .iter()
+        .flat_map(|pattern| pattern.split('\n').map(String::from))
+        .collect();
+    // De donde salio cada patron de un archivo, para ubicar sus errores
+    let mut origins: Vec<(usize, &str, usize)> = vec![];
+    for file in &config.pattern_files {
+        for (line, pattern) in read_pattern_file(file)?.into_iter().enumerate() {
+            origins.push((patterns.len(), file, line + 1));
+            patterns.push(pattern);
+        }
+    }
+
+    let boundary = match (config.line_regexp, config.word_regexp) {
+        (true, _) => MatchBoundary::Line,
+        (false, true) => MatchBoundary::Word,
+        (false, false) => MatchBoundary::None,
+    };
+
+    if (config.fixed_strings && !config.ignore_case) || patterns.is_empty() {
+        return Ok(Box::new(
+            LiteralMatcher::new(&patterns)
+                .with_semantics(MatchSemantics::LeftmostLongest)
+                .with_boundary(boundary),
+        ));
+    }
+
+    // El LiteralMatcher compara bytes, asi que -F con -i busca los literales como regex
+    if config.fixed_strings {
+        patterns = patterns.iter().map(|pattern| escape(pattern)).collect();
+    }
+    let regex = Regex::new_many(&patterns).map_err(|err| -> Box<dyn Error> {
+        let origin = origins
+            .iter()
+            .find(|(index, _, _)| Some(*index) == err.index);
+        match origin {
+            Some((_, file, line)) => format!("{}:{}: {}", file, line, err).into(),
+            None => err.into(),
+        }
+    })?;
+    Ok(Box::new(
+        regex
+            .with_semantics(MatchSemantics::LeftmostLongest)
+            .with_boundary(boundary)
+            .with_ignore_case(config.ignore_case)
+            // Fuera del modo multilinea los matches no pasan de una linea a la siguiente
+            .with_line_terminator((!config.multiline).then_some(b'\n')),
+    ))
+}
@@ -0,0 +1,129 @@
+use crate::regex_match::{Captures, Match};
+use crate::utils::decode_char;
+
+/// Interfaz comun a todos los buscadores (expresiones regulares y cadenas fijas)
+pub trait Matcher {
+    /// FIND_AT: devuelve la posicion del primer match que empiece en `start` o despues.
+    /// Lo que esta antes de `start` sigue siendo visible para las anclas
+    fn find_at(&self, haystack: &[u8], start: usize) -> Option<Match>;
+
+    /// CAPTURES_AT: como `find_at`, pero devuelve tambien los grupos de captura.
+    /// Por defecto solo existe el grupo 0
+    fn captures_at(&self, haystack: &[u8], start: usize) -> Option<Captures> {
+        self.find_at(haystack, start)
+            .map(|found| Captures::new(vec![Some(found.start), Some(found.end)]))
+    }
+
+    /// CAPTURES_LEN: cantidad de grupos de captura, incluyendo el match completo
+    fn captures_len(&self) -> usize {
+        1
+    }
+
+    /// CAPTURE_INDEX: numero del grupo de captura con ese nombre, si existe
+    fn capture_index(&self, _name: &str) -> Option<usize> {
+        None
+    }
+
+    fn is_match(&self, haystack: &[u8]) -> bool {
+        self.find_at(haystack, 0).is_some()
+    }
+
+    /// FIND: devuelve la posicion del primer match en el texto
+    fn find(&self, haystack: &[u8]) -> Option<Match> {
+        self.find_at(haystack, 0)
+    }
+
+    /// CAPTURES: devuelve las posiciones de los grupos de captura del primer match
+    fn captures(&self, haystack: &[u8]) -> Option<Captures> {
+        self.captures_at(haystack, 0)
+    }
+
+    /// FIND_ITER: itera sobre todos los matches del texto que no se superponen
+    fn find_iter<'m, 'h>(&'m self, haystack: &'h [u8]) -> Matches<'m, 'h, Self>
+    where
+        Self: Sized,
+    {
+        Matches {
+            matcher: self,
+            haystack,
+            at: 0,
+            last_end: None,
+        }
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for Box<M> {
+    fn find_at(&self, haystack: &[u8], start: usize) -> Option<Match> {
+        (**self).find_at(haystack, start)
+    }
+
+    fn captures_at(&self, haystack: &[u8], start: usize) -> Option<Captures> {
+        (**self).captures_at(haystack, start)
+    }
+
+    fn captures_len(&self) -> usize {
+        (**self).captures_len()
+    }
+
+    fn capture_index(&self, name: &str) -> Option<usize> {
+        (**self).capture_index(name)
+    }
+}
+
+impl<M: Matcher + ?Sized> Matcher for &M {
+    fn find_at(&self, haystack: &[u8], start: usize) -> Option<Match> {
+        (**self).find_at(haystack, start)
+    }
+
+    fn captures_at(&self, haystack: &[u8], start: usize) -> Option<Captures> {
+        (**self).captures_at(haystack, start)
+    }
+
+    fn captures_len(&self) -> usize {
+        (**self).captures_len()
+    }
+
+    fn capture_index(&self, name: &str) -> Option<usize> {
+        (**self).capture_index(name)
+    }
+}
+
+/// Iterador sobre los matches de un buscador, ver `Matcher::find_iter`
+#[derive(Debug)]
+pub struct Matches<'m, 'h, M: ?Sized> {
+    matcher: &'m M,
+    haystack: &'h [u8],
+    at: usize,
+    last_end: Option<usize>,
+}
+
+impl<M: Matcher + ?Sized> Iterator for Matches<'_, '_, M> {
+    type Item = Match;
+
+    fn next(&mut self) -> Option<Match> {
+        loop {
+            if self.at > self.haystack.len() {
+                return None;
+            }
+
+            let found = self.matcher.find_at(self.haystack, self.at)?;
+
+            if found.is_empty() {
+                // Se avanza un caracter para no devolver el mismo match vacio para siempre
+                self.at = match decode_char(&self.haystack[found.end..]) {
+                    Some((_, size)) => found.end + size,
+                    None => found.end + 1,
+                };
+                // Un match vacio justo despues del anterior no cuenta
+                if self.last_end == Some(found.end) {
+                    continue;
+                }
+            } else {
+                self.at = found.end;
+            }
+
+            self.last_end = Some(found.end);
+            return Some(found);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use std::fs::{File, Metadata};
+use std::io;
+use std::ops::Deref;
+
+/// Tamaño minimo para que el modo automatico mapee un archivo; en los chicos leer es mas rapido
+pub const MMAP_MIN_SIZE: u64 = 256 * 1024;
+
+/// Cantidad maxima de archivos de una busqueda para que el modo automatico los mapee. Mapear
+/// y desmapear muchos archivos cuesta mas que leerlos
+pub const MMAP_MAX_FILES: usize = 16;
+
+/// Cuando buscar en un archivo mapeado en memoria en lugar de leerlo
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MmapChoice {
+    #[default]
+    Auto, // Segun el tamaño del archivo y la cantidad de archivos
+    Always, // Siempre que se pueda (archivos comunes)
+    Never,
+}
+
+impl MmapChoice {
+    /// SHOULD_MAP: indica si conviene mapear un archivo de una busqueda sobre `files` archivos.
+    /// Los pipes y los archivos especiales nunca se mapean
+    pub fn should_map(&self, metadata: &Metadata, files: usize) -> bool {
+        if !metadata.is_file() {
+            return false;
+        }
+        match self {
+            MmapChoice::Auto => metadata.len() >= MMAP_MIN_SIZE && files <= MMAP_MAX_FILES,
+            MmapChoice::Always => true,
+            MmapChoice::Never => false,
+        }
+    }
+}
+
+/// Contenido de un archivo mapeado en memoria solo para lectura.
+///
+/// Si otro proceso achica el archivo mientras esta mapeado, leer la parte que ya no existe
+/// termina el programa con SIGBUS; es el mismo riesgo que aceptan otras herramientas de busqueda
+pub struct Mmap {
+    ptr: *const u8,
+    len: usize,
+}
+
+// El mapeo es de solo lectura, se puede compartir entre hilos
+unsafe impl Send for Mmap {}
+unsafe impl Sync for Mmap {}
+
+impl Mmap {
+    /// MAP: mapea el archivo entero. Falla si el sistema no lo permite
+    pub fn map(file: &File) -> io::Result<Mmap> {
+        let len = usize::try_from(file.metadata()?.len())
+            .map_err(|_| io::Error::other("file too large to map"))?;
+
+        // mmap no acepta largo 0
+        if len == 0 {
+            return Ok(Mmap {
+                ptr: std::ptr::null(),
+                len: 0,
+            });
+        }
+
+        let ptr = sys::map(file, len)?;
+        Ok(Mmap { ptr, len })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        // SAFETY: `ptr` apunta a `len` bytes mapeados que siguen validos hasta el Drop
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.len > 0 {
+            sys::unmap(self.ptr, self.len);
+        }
+    }
+}
+
+/// MMAP y MUNMAP de la libc. El crate no tiene dependencias, asi que las funciones y las
+/// constantes se declaran aca; los valores de PROT_READ y MAP_PRIVATE son los mismos en Linux y
+/// macOS, y el offset es de 64 bits porque solo se compila en plataformas de 64 bits
+#[cfg(all(unix, target_pointer_width = "64"))]
+mod sys {
+    use std::ffi::{c_int, c_void};
+    use std::fs::File;
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    const PROT_READ: c_int = 1;
+    const MAP_PRIVATE: c_int = 2;
+    const MAP_FAILED: *mut c_void = !0 as *mut c_void;
+
+    extern "C" {
+        fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: i64,
+        ) -> *mut c_void;
+        fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+
+    pub fn map(file: &File, len: usize) -> io::Result<*const u8> {
+        // SAFETY: se pide un mapeo nuevo de solo lectura; el resultado se valida antes de usarlo
+        let ptr = unsafe {
+            mmap(
+                std::ptr::null_mut(),
+                len,
+                PROT_READ,
+                MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == MAP_FAILED {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(ptr as *const u8)
+    }
+
+    pub fn unmap(ptr: *const u8, len: usize) {
+        // SAFETY: `ptr` y `len` son los de un mapeo hecho por `map` que no se vuelve a usar
+        unsafe {
+            munmap(ptr as *mut c_void, len);
+        }
+    }
+}
+
+#[cfg(not(all(unix, target_pointer_width = "64")))]
+mod sys {
+    use std::fs::File;
+    use std::io;
+
+    pub fn map(_file: &File, _len: usize) -> io::Result<*const u8> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "memory maps are not supported on this platform",
+        ))
+    }
+
+    pub fn unmap(_ptr: *const u8, _len: usize) {}
+}
@@ -0,0 +1,200 @@
+use crate::utils::rfind_byte;
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::sync::{Mutex, MutexGuard};
+
+/// Cuantos bytes junta cada archivo antes de escribirlos, si no se indica otra cosa
+pub const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Salida compartida entre los hilos de la busqueda. Cada archivo junta su salida en un buffer
+/// y la escribe por partes de lineas enteras, asi la memoria no crece con el tamaño del
+/// archivo. Para que la salida de cada archivo quede junta, escribe un solo archivo a la vez:
+/// el primero que empieza a escribir, o en modo ordenado el que le toca. Los demas guardan su
+/// salida hasta que el destino se libera
+pub struct SharedOutput<W: Write> {
+    ordered: bool,
+    chunk_size: usize,
+    separator: Option<Vec<u8>>, // Entre las salidas de dos archivos
+    state: Mutex<State<W>>,
+}
+
+struct State<W> {
+    writer: W,
+    next: usize,                       // Proximo archivo a escribir en modo ordenado
+    owner: Option<usize>,              // Archivo que esta escribiendo, sin modo ordenado
+    pending: BTreeMap<usize, Vec<u8>>, // Archivos terminados antes de su turno
+    written: bool,                     // Si ya se escribio la salida de algun archivo
+}
+
+impl<W: Write> SharedOutput<W> {
+    pub fn new(writer: W, ordered: bool) -> Self {
+        SharedOutput {
+            ordered,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            separator: None,
+            state: Mutex::new(State {
+                writer,
+                next: 0,
+                owner: None,
+                pending: BTreeMap::new(),
+                written: false,
+            }),
+        }
+    }
+
+    /// SEPARATOR: escribe `separator` entre las salidas de dos archivos, como el separador de
+    /// grupos de contexto. Los archivos sin salida no se separan
+    pub fn separator(mut self, separator: Option<Vec<u8>>) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// CHUNK_SIZE: cuantos bytes junta cada archivo antes de escribir sus lineas completas. Con
+    /// 0 cada linea se escribe apenas se completa, lo que conviene con un solo hilo
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// FILE: la salida del archivo `index`. En modo ordenado hay que terminar la salida de todos
+    /// los archivos, aunque no escriban nada, para que avance el turno
+    pub fn file(&self, index: usize) -> FileOutput<'_, W> {
+        FileOutput {
+            output: self,
+            index,
+            buffer: vec![],
+            started: false,
+            error: None,
+        }
+    }
+
+    /// WRITE: entrega la salida completa del archivo `index`
+    pub fn write(&self, index: usize, bytes: Vec<u8>) -> io::Result<()> {
+        let mut file = self.file(index);
+        file.buffer = bytes;
+        file.finish()
+    }
+
+    /// INTO_INNER: devuelve el destino de la salida
+    pub fn into_inner(self) -> W {
+        self.state
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .writer
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State<W>> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// MAY_WRITE: indica si el archivo `index` puede escribir ahora
+    fn may_write(&self, state: &State<W>, index: usize) -> bool {
+        match self.ordered {
+            true => state.next == index,
+            false => state.owner.is_none_or(|owner| owner == index),
+        }
+    }
+
+    /// WRITE_FILE: escribe parte de la salida de un archivo, separada de la del anterior si es
+    /// lo primero que escribe
+    fn write_file(&self, state: &mut State<W>, bytes: &[u8], first: bool) -> io::Result<()> {
+        if bytes.is_empty() {
+            return Ok(());
+        }
+        if let (true, true, Some(separator)) = (first, state.written, &self.separator) {
+            state.writer.write_all(separator)?;
+        }
+        state.written = true;
+        state.writer.write_all(bytes)
+    }
+}
+
+/// La salida de un archivo dentro de una `SharedOutput`. Hay que terminarla con `finish`
+pub struct FileOutput<'o, W: Write> {
+    output: &'o SharedOutput<W>,
+    index: usize,
+    buffer: Vec<u8>,
+    started: bool,            // Si ya se escribio algo de este archivo
+    error: Option<io::Error>, // El primer error al escribir en el destino
+}
+
+impl<W: Write> FileOutput<'_, W> {
+    /// FINISH: escribe lo que falta del archivo. Devuelve el error del destino si alguna
+    /// escritura fallo, para distinguirlo de los errores al leer el archivo
+    pub fn finish(mut self) -> io::Result<()> {
+        if let Some(err) = self.error.take() {
+            return Err(err);
+        }
+
+        let output = self.output;
+        let mut state = output.lock();
+        if !output.may_write(&state, self.index) {
+            let buffer = std::mem::take(&mut self.buffer);
+            state.pending.insert(self.index, buffer);
+            return Ok(());
+        }
+
+        output.write_file(&mut state, &self.buffer, !self.started)?;
+
+        // Se libera el destino para los archivos que terminaron mientras esperaban
+        if !output.ordered {
+            state.owner = None;
+            while let Some((_, bytes)) = state.pending.pop_first() {
+                output.write_file(&mut state, &bytes, true)?;
+            }
+            return Ok(());
+        }
+        state.next += 1;
+        loop {
+            let next = state.next;
+            let Some(bytes) = state.pending.remove(&next) else {
+                break;
+            };
+            output.write_file(&mut state, &bytes, true)?;
+            state.next += 1;
+        }
+        Ok(())
+    }
+
+    /// WRITE_LINES: escribe las lineas completas del buffer si el archivo puede escribir, y
+    /// sin modo ordenado se queda con el destino hasta terminar
+    fn write_lines(&mut self) -> io::Result<()> {
+        let Some(end) = rfind_byte(b'\n', &self.buffer).map(|i| i + 1) else {
+            return Ok(());
+        };
+
+        let output = self.output;
+        let mut state = output.lock();
+        if !output.may_write(&state, self.index) {
+            return Ok(());
+        }
+        if !output.ordered {
+            state.owner = Some(self.index);
+        }
+        output.write_file(&mut state, &self.buffer[..end], !self.started)?;
+        self.started = true;
+        drop(state);
+        self.buffer.drain(..end);
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for FileOutput<'_, W> {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(bytes);
+        if self.buffer.len() >= self.output.chunk_size {
+            if let Err(err) = self.write_lines() {
+                let kind = err.kind();
+                self.error.get_or_insert(err);
+                return Err(kind.into());
+            }
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::thread;
+
+/// Reparte una lista de trabajos entre varios hilos. Cada hilo empieza con una porcion contigua
+/// de la lista en su propia cola y, cuando la termina, le roba trabajos del final de la cola de
+/// otro. Asi un hilo que toco archivos grandes no deja a los demas esperando
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WorkStealingPool {
+    threads: usize,
+}
+
+impl WorkStealingPool {
+    pub fn new(threads: usize) -> Self {
+        WorkStealingPool {
+            threads: threads.max(1),
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// RUN: ejecuta `job` una vez por trabajo, con su posicion en la lista. Vuelve cuando
+    /// terminaron todos
+    pub fn run<T, F>(&self, jobs: Vec<T>, job: F)
+    where
+        T: Send,
+        F: Fn(usize, T) + Sync,
+    {
+        let threads = self.threads.min(jobs.len());
+        if threads <= 1 {
+            for (index, item) in jobs.into_iter().enumerate() {
+                job(index, item);
+            }
+            return;
+        }
+
+        let chunk = jobs.len().div_ceil(threads);
+        let mut queues: Vec<Mutex<VecDeque<(usize, T)>>> = vec![];
+        let mut jobs = jobs.into_iter().enumerate().peekable();
+        while jobs.peek().is_some() {
+            queues.push(Mutex::new(jobs.by_ref().take(chunk).collect()));
+        }
+
+        let queues = &queues;
+        let job = &job;
+        thread::scope(|scope| {
+            for own in 0..queues.len() {
+                scope.spawn(move || {
+                    while let Some((index, item)) = next_job(queues, own) {
+                        job(index, item);
+                    }
+                });
+            }
+        });
+    }
+}
+
+impl Default for WorkStealingPool {
+    /// Un hilo por cada nucleo disponible
+    fn default() -> Self {
+        WorkStealingPool::new(available_threads())
+    }
+}
+
+/// AVAILABLE_THREADS: cantidad de hilos que el sistema puede ejecutar a la vez
+pub fn available_threads() -> usize {
+    thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+}
+
+/// NEXT_JOB: toma el primer trabajo de la cola propia o, si esta vacia, el ultimo de otra.
+/// Como no se agregan trabajos, cuando todas las colas estan vacias no queda nada por hacer
+fn next_job<T>(queues: &[Mutex<VecDeque<(usize, T)>>], own: usize) -> Option<(usize, T)> {
+    if let Some(job) = lock(&queues[own]).pop_front() {
+        return Some(job);
+    }
+
+    (1..queues.len())
+        .map(|offset| &queues[(own + offset) % queues.len()])
+        .find_map(|queue| lock(queue).pop_back())
+}
+
+/// LOCK: un hilo que entro en panico no deja la cola inconsistente, se sigue usando
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
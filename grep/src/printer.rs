@@ -0,0 +1,411 @@
+use crate::color::Colors;
+use crate::config::{BinaryFiles, Config};
+use crate::matcher::Matcher;
+use crate::regex_match::Match;
+use crate::searcher::{Sink, SinkContext, SinkMatch};
+use crate::template::{Record, Template};
+use crate::utils::{find_byte, rfind_byte};
+use std::io::{self, Write};
+
+/// Escribe los resultados de un archivo con el formato de grep: cada linea seleccionada,
+/// precedida por el nombre del archivo, el numero de linea, la columna y la posicion en bytes
+/// segun las opciones. Las lineas de contexto llevan '-' en lugar de ':' despues de cada dato
+/// del prefijo. Con -o se escribe cada match en su propia linea, sin contexto, y con --format
+/// cada match completa la plantilla. Con -c solo cuenta, con -l o -L solo escribe el nombre y
+/// con -q no escribe nada. De un archivo binario solo se avisa si matchea
+pub struct Printer<'a, W: Write> {
+    config: &'a Config,
+    matcher: &'a dyn Matcher, // Para ubicar los matches dentro de las lineas
+    name: &'a str,
+    out: W,
+    matches: u64,               // Lineas seleccionadas hasta ahora
+    colors: Option<&'a Colors>, // Sin colores, la salida no lleva secuencias de escape
+    template: Option<&'a Template>,
+    binary: bool, // Si la fuente es binaria y no se escriben sus lineas
+}
+
+/// Ubicacion de una linea que se muestra como prefijo
+#[derive(Debug, Clone, Copy)]
+struct Location {
+    line_number: Option<u64>,
+    column: Option<usize>,
+    byte_offset: u64,
+}
+
+/// LINE_MATCHES: los matches dentro de las lineas de un resultado, sin contar el ultimo '\n'
+pub fn line_matches(matcher: &dyn Matcher, bytes: &[u8]) -> Vec<Match> {
+    let text = bytes.strip_suffix(b"\n").unwrap_or(bytes);
+    Matcher::find_iter(&matcher, text).collect()
+}
+
+/// SHOWN_MATCHES: los matches que se marcan en una linea seleccionada (`selected`) o de
+/// contexto. Con -v las lineas seleccionadas son las que no matchean, y las de contexto las que
+/// si, asi que solo estas tienen matches
+pub fn shown_matches(
+    matcher: &dyn Matcher,
+    bytes: &[u8],
+    invert_match: bool,
+    selected: bool,
+) -> Vec<Match> {
+    match selected != invert_match {
+        true => line_matches(matcher, bytes),
+        false => vec![],
+    }
+}
+
+impl<'a, W: Write> Printer<'a, W> {
+    pub fn new(config: &'a Config, matcher: &'a dyn Matcher, name: &'a str, out: W) -> Self {
+        Printer {
+            config,
+            matcher,
+            name,
+            out,
+            matches: 0,
+            colors: None,
+            template: None,
+            binary: false,
+        }
+    }
+
+    /// COLORS: resalta los matches y los prefijos con secuencias de escape ANSI
+    pub fn colors(mut self, colors: Option<&'a Colors>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// TEMPLATE: escribe cada match con la plantilla de --format en lugar de las lineas
+    pub fn template(mut self, template: Option<&'a Template>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// MATCHES: cantidad de lineas seleccionadas
+    pub fn matches(&self) -> u64 {
+        self.matches
+    }
+
+    /// FINISH: escribe lo que depende del archivo entero: el nombre con -l o -L, o la cuenta
+    /// de -c
+    pub fn finish(&mut self) -> io::Result<()> {
+        let config = self.config;
+        if config.quiet {
+            return Ok(());
+        }
+
+        if config.files_with_matches || config.files_without_match {
+            if config.files_with_matches == (self.matches > 0) {
+                self.paint(|colors| &colors.file_name, self.name.as_bytes())?;
+                self.out
+                    .write_all(if config.null { b"\0" } else { b"\n" })?;
+            }
+        } else if config.count {
+            self.write_name(b':')?;
+            writeln!(self.out, "{}", self.matches)?;
+        }
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    /// WRITE_LINES: escribe cada linea del resultado con su prefijo. La columna es la del
+    /// primer match, y no existe en las lineas que no matchean (-v)
+    fn write_lines(&mut self, found: &SinkMatch) -> io::Result<()> {
+        let wants_matches = self.config.column || self.colors.is_some();
+        let matches = match wants_matches {
+            true => shown_matches(self.matcher, found.bytes, self.config.invert_match, true),
+            false => vec![],
+        };
+
+        let mut location = Location {
+            line_number: found.line_number,
+            column: self
+                .config
+                .column
+                .then(|| matches.first().map(|m| m.start + 1))
+                .flatten(),
+            byte_offset: found.absolute_offset,
+        };
+        // En modo multilinea un match puede traer varias lineas, cada una lleva su prefijo
+        let mut start = 0;
+        for line in found.bytes.split_inclusive(|b| *b == b'\n') {
+            self.write_prefix(b':', &location)?;
+            self.write_highlighted(line, start, &matches, true)?;
+
+            location.line_number = location.line_number.map(|n| n + 1);
+            location.column = None;
+            location.byte_offset += line.len() as u64;
+            start += line.len();
+        }
+        Ok(())
+    }
+
+    /// WRITE_VIMGREP: una fila por cada match, con la linea y la columna donde empieza
+    fn write_vimgrep(&mut self, found: &SinkMatch) -> io::Result<()> {
+        // Las lineas que no matchean (-v) no tienen columna
+        let matches = match self.config.invert_match {
+            true => vec![Match::new(0, 0)],
+            false => line_matches(self.matcher, found.bytes),
+        };
+
+        for m in &matches {
+            let before = &found.bytes[..m.start];
+            let line_start = rfind_byte(b'\n', before).map_or(0, |i| i + 1);
+            let line_end = find_byte(b'\n', &found.bytes[line_start..])
+                .map_or(found.bytes.len(), |i| line_start + i + 1);
+            let newlines = before.iter().filter(|b| **b == b'\n').count() as u64;
+
+            let location = Location {
+                line_number: found.line_number.map(|n| n + newlines),
+                column: (!self.config.invert_match).then_some(m.start - line_start + 1),
+                byte_offset: found.absolute_offset + line_start as u64,
+            };
+            self.write_prefix(b':', &location)?;
+            let line = &found.bytes[line_start..line_end];
+            self.write_highlighted(line, line_start, &matches, true)?;
+        }
+        Ok(())
+    }
+
+    /// WRITE_ONLY_MATCHING: una linea por cada match que no este vacio, con la posicion en
+    /// bytes del match en lugar de la de la linea
+    fn write_only_matching(&mut self, found: &SinkMatch) -> io::Result<()> {
+        let show_column = self.config.column || self.config.vimgrep;
+        for m in line_matches(self.matcher, found.bytes) {
+            if m.is_empty() {
+                continue;
+            }
+            let before = &found.bytes[..m.start];
+            let line_start = rfind_byte(b'\n', before).map_or(0, |i| i + 1);
+            let newlines = before.iter().filter(|b| **b == b'\n').count() as u64;
+
+            let mut location = Location {
+                line_number: found.line_number.map(|n| n + newlines),
+                column: show_column.then_some(m.start - line_start + 1),
+                byte_offset: found.absolute_offset + m.start as u64,
+            };
+            // Un match multilinea lleva el prefijo en cada una de sus lineas
+            let mut start = m.start;
+            for part in found.bytes[m.start..m.end].split_inclusive(|b| *b == b'\n') {
+                self.write_prefix(b':', &location)?;
+                self.write_highlighted(part, start, std::slice::from_ref(&m), true)?;
+
+                location.line_number = location.line_number.map(|n| n + 1);
+                location.column = location.column.map(|_| 1);
+                location.byte_offset += part.len() as u64;
+                start += part.len();
+            }
+        }
+        Ok(())
+    }
+
+    /// WRITE_TEMPLATE: un registro de la plantilla por cada match que no este vacio. Las lineas
+    /// que no matchean (-v) tienen un solo registro, con el match vacio al principio
+    fn write_template(&mut self, template: &Template, found: &SinkMatch) -> io::Result<()> {
+        let text = found.bytes.strip_suffix(b"\n").unwrap_or(found.bytes);
+        let captures = match self.config.invert_match {
+            true => vec![None],
+            false => line_matches(self.matcher, found.bytes)
+                .into_iter()
+                .filter(|m| !m.is_empty())
+                .map(|m| self.matcher.captures_at(text, m.start))
+                .collect(),
+        };
+
+        for captures in &captures {
+            let start = captures
+                .as_ref()
+                .and_then(|captures| captures.get(0))
+                .map_or(0, |m| m.start);
+            let before = &found.bytes[..start];
+            let line_start = rfind_byte(b'\n', before).map_or(0, |i| i + 1);
+            let newlines = before.iter().filter(|b| **b == b'\n').count() as u64;
+
+            let record = Record {
+                path: self.name,
+                line_number: found.line_number.map(|n| n + newlines),
+                column: start - line_start + 1,
+                byte_offset: found.absolute_offset + start as u64,
+                text,
+                haystack: text,
+                captures: captures.as_ref(),
+            };
+            template.render(&mut self.out, &record)?;
+        }
+        Ok(())
+    }
+
+    /// WRITE_PREFIX: el nombre del archivo, el numero de linea, la columna y la posicion en
+    /// bytes que correspondan, cada uno seguido de `separator`
+    fn write_prefix(&mut self, separator: u8, location: &Location) -> io::Result<()> {
+        self.write_name(separator)?;
+        if let Some(line_number) = location.line_number {
+            self.paint(
+                |colors| &colors.line_number,
+                line_number.to_string().as_bytes(),
+            )?;
+            self.paint(|colors| &colors.separator, &[separator])?;
+        }
+        if let Some(column) = location.column {
+            self.paint(|colors| &colors.line_number, column.to_string().as_bytes())?;
+            self.paint(|colors| &colors.separator, &[separator])?;
+        }
+        if self.config.byte_offset {
+            let byte_offset = location.byte_offset.to_string();
+            self.paint(|colors| &colors.byte_offset, byte_offset.as_bytes())?;
+            self.paint(|colors| &colors.separator, &[separator])?;
+        }
+        Ok(())
+    }
+
+    /// WRITE_NAME: el nombre del archivo seguido de `separator` (o '\0' con -Z), si se muestra
+    fn write_name(&mut self, separator: u8) -> io::Result<()> {
+        if self.config.show_filename() {
+            self.paint(|colors| &colors.file_name, self.name.as_bytes())?;
+            match self.config.null {
+                true => self.out.write_all(b"\0")?,
+                false => self.paint(|colors| &colors.separator, &[separator])?,
+            }
+        }
+        Ok(())
+    }
+
+    /// PAINT: escribe `text` con el color que elige `color`, si la salida lleva colores
+    fn paint(&mut self, color: fn(&Colors) -> &String, text: &[u8]) -> io::Result<()> {
+        match self.colors {
+            Some(colors) => colors.paint(&mut self.out, color(colors), text),
+            None => self.out.write_all(text),
+        }
+    }
+
+    /// PRINTS_LINES: indica si se escriben las lineas o solo un resumen del archivo
+    fn prints_lines(&self) -> bool {
+        let config = self.config;
+        !(config.quiet || config.count || config.files_with_matches || config.files_without_match)
+    }
+
+    /// PRINTS_CONTEXT: indica si se escriben las lineas de contexto, que no se escriben con -o,
+    /// con --format ni en un archivo binario
+    fn prints_context(&self) -> bool {
+        let config = self.config;
+        self.prints_lines() && !config.only_matching && self.template.is_none() && !self.binary
+    }
+
+    /// WRITE_HIGHLIGHTED: escribe una linea que empieza en `start` dentro del resultado,
+    /// resaltando las partes de `matches` (con posiciones dentro del resultado). `selected`
+    /// elige entre los colores de las lineas seleccionadas y los de las de contexto
+    fn write_highlighted(
+        &mut self,
+        line: &[u8],
+        start: usize,
+        matches: &[Match],
+        selected: bool,
+    ) -> io::Result<()> {
+        let Some(colors) = self.colors else {
+            return self.write_text(line);
+        };
+        let (selected_line, context_line) = colors.line_colors(self.config.invert_match);
+        let (match_color, line_color) = match selected {
+            true => (&colors.selected_match, selected_line),
+            false => (&colors.context_match, context_line),
+        };
+
+        let text = line.strip_suffix(b"\n").unwrap_or(line);
+        let end = start + text.len();
+        let mut pos = start;
+        for m in matches {
+            if m.end <= pos || m.start >= end {
+                continue;
+            }
+            let (match_start, match_end) = (m.start.max(pos), m.end.min(end));
+            colors.paint(
+                &mut self.out,
+                line_color,
+                &text[pos - start..match_start - start],
+            )?;
+            let matched = &text[match_start - start..match_end - start];
+            colors.paint(&mut self.out, match_color, matched)?;
+            pos = match_end;
+        }
+        colors.paint(&mut self.out, line_color, &text[pos - start..])?;
+        self.out.write_all(b"\n")
+    }
+
+    /// WRITE_TEXT: escribe una linea, agregando el '\n' si le falta
+    fn write_text(&mut self, line: &[u8]) -> io::Result<()> {
+        self.out.write_all(line)?;
+        if !line.ends_with(b"\n") {
+            self.out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+impl<W: Write> Sink for Printer<'_, W> {
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool> {
+        self.matches += 1;
+
+        // Con -q, -l o -L alcanza con saber que hay un match
+        let config = self.config;
+        if config.quiet || config.files_with_matches || config.files_without_match {
+            return Ok(false);
+        }
+        if self.prints_lines() && self.binary {
+            // Alcanza con el primer match para avisar
+            writeln!(self.out, "Binary file {} matches", self.name)?;
+            return Ok(false);
+        }
+        if self.prints_lines() {
+            if let Some(template) = self.template {
+                self.write_template(template, found)?;
+            } else if config.only_matching {
+                self.write_only_matching(found)?;
+            } else if config.vimgrep {
+                self.write_vimgrep(found)?;
+            } else {
+                self.write_lines(found)?;
+            }
+        }
+
+        Ok(config.max_count.is_none_or(|max| self.matches < max))
+    }
+
+    fn context(&mut self, context: &SinkContext) -> io::Result<bool> {
+        if !self.prints_context() {
+            return Ok(true);
+        }
+        let location = Location {
+            line_number: context.line_number,
+            column: None,
+            byte_offset: context.absolute_offset,
+        };
+        self.write_prefix(b'-', &location)?;
+        let matches = match self.colors.is_some() {
+            true => shown_matches(self.matcher, context.bytes, self.config.invert_match, false),
+            false => vec![],
+        };
+        self.write_highlighted(context.bytes, 0, &matches, false)?;
+        Ok(true)
+    }
+
+    fn context_break(&mut self) -> io::Result<bool> {
+        if self.binary {
+            return Ok(true);
+        }
+        if let Some(separator) = self.config.group_separator() {
+            self.paint(|colors| &colors.separator, separator.as_bytes())?;
+            self.out.write_all(b"\n")?;
+        }
+        Ok(true)
+    }
+
+    fn binary_data(&mut self, _offset: u64) -> io::Result<bool> {
+        match self.config.binary_files {
+            BinaryFiles::Binary => self.binary = true,
+            BinaryFiles::Text => {}
+            BinaryFiles::WithoutMatch => return Ok(false),
+        }
+        Ok(true)
+    }
+}
@@ -1,404 +1,185 @@
-use crate::evaluated_step::EvaluatedStep;
-use crate::regex_class::RegexClass;
-use crate::regex_rep::RegexRep;
+use crate::matcher::Matcher;
+use crate::regex_ast;
+pub use crate::regex_error::{Error, PatternError, Span};
+use crate::regex_hir::{Hir, HirClass};
+use crate::regex_match::{Captures, Match};
+pub use crate::regex_program::{MatchBoundary, MatchSemantics};
+use crate::regex_program::{RegexProgram, SIZE_LIMIT};
 use crate::regex_step::RegexStep;
-use crate::regex_val::RegexVal;
-use std::collections::VecDeque;
-use std::io::{Error, ErrorKind};
+
+/// ESCAPE: escapa los metacaracteres del texto para poder usarlo como parte de un patron
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.[](){}*+?|^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
 
 #[derive(Debug, Clone)]
 pub struct Regex {
     expression_steps: Vec<Vec<RegexStep>>,
+    program: RegexProgram,
+    folded: Option<RegexProgram>, // El programa sin distinguir mayusculas, si se pidio
+    semantics: MatchSemantics,
+    boundary: MatchBoundary,
+    line_terminator: Option<u8>, // Byte que ningun match consume
+    names: Vec<(String, usize)>, // Nombre y numero de los grupos con nombre
 }
 
 impl Regex {
-    pub fn new(expression: &str) -> Result<Self, std::io::Error> {
-        let mut expression_steps: Vec<Vec<RegexStep>> = vec![];
-
-        let aux_expressions: Vec<&str> = expression.split('|').collect();
-
-        for aux_expression in aux_expressions {
-            let mut steps: Vec<RegexStep> = vec![];
-            // Recorremos la expression char por char con .next()
-            let mut char_iterator = aux_expression.chars();
-
-            if !&aux_expression.starts_with('^') {
-                let first_step = RegexStep {
-                    rep: RegexRep::Any,
-                    val: RegexVal::Wildcard,
-                };
-
-                steps.push(first_step)
-            } else {
-                char_iterator.next();
-            }
-
-            while let Some(c) = char_iterator.next() {
-                let step = match c {
-                    '.' => Some(RegexStep {
-                        rep: RegexRep::Exact(1),
-                        val: RegexVal::Wildcard,
-                    }),
-                    'a'..='z' | ' ' => Some(RegexStep {
-                        rep: RegexRep::Exact(1),
-                        val: RegexVal::Literal(c),
-                    }),
-                    '*' => {
-                        if let Some(last) = steps.last_mut() {
-                            last.rep = RegexRep::Any;
-                        } else {
-                            return Err(Error::new(ErrorKind::Other, "Unexpected '*' found"));
-                        }
-                        None
-                    }
-                    '\\' => match char_iterator.next() {
-                        Some(literal) => Some(RegexStep {
-                            rep: RegexRep::Exact(1),
-                            val: RegexVal::Literal(literal),
-                        }),
-                        None => {
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                "Unexpected character found (1)",
-                            ))
-                        }
-                    },
-                    '[' => match char_iterator.next() {
-                        Some(char) => match char {
-                            '[' => {
-                                let next_five_chars: String =
-                                    char_iterator.by_ref().take(7).collect();
-
-                                let class_type = match next_five_chars.as_str() {
-                                    ":alnum:" => RegexClass::Alphanumeric,
-                                    ":alpha:" => RegexClass::Alphabetic,
-                                    ":digit:" => RegexClass::Digit,
-                                    ":lower:" => RegexClass::Lowercase,
-                                    ":upper:" => RegexClass::Uppercase,
-                                    ":space:" => RegexClass::Whitespace,
-                                    ":punct:" => RegexClass::Punctuation,
-                                    _ => {
-                                        return Err(Error::new(
-                                            ErrorKind::Other,
-                                            "Unexpected class found",
-                                        ))
-                                    }
-                                };
-
-                                char_iterator.next();
-                                char_iterator.next();
-
-                                Some(RegexStep {
-                                    rep: RegexRep::Exact(1),
-                                    val: RegexVal::Class(class_type),
-                                })
-                            }
-                            _ => {
-                                let mut allowed_chars: Vec<char> = vec![];
-                                let mut not_allowed = false;
-
-                                match char {
-                                    ']' => break,
-                                    '^' => not_allowed = true,
-                                    _ => allowed_chars.push(char),
-                                }
-
-                                while let Some(next_char) = char_iterator.next() {
-                                    match next_char {
-                                        ']' => break,
-                                        _ => allowed_chars.push(next_char),
-                                    }
-                                }
-
-                                if not_allowed {
-                                    Some(RegexStep {
-                                        rep: RegexRep::Exact(1),
-                                        val: RegexVal::NotAllowed(allowed_chars),
-                                    })
-                                } else {
-                                    Some(RegexStep {
-                                        rep: RegexRep::Exact(1),
-                                        val: RegexVal::Allowed(allowed_chars),
-                                    })
-                                }
-                            }
-                        },
-                        None => {
-                            return Err(Error::new(
-                                ErrorKind::Other,
-                                "Unexpected character found (2)",
-                            ))
-                        }
-                    },
-                    '{' => {
-                        let last_val: RegexVal;
-                        if let Some(last) = steps.last_mut() {
-                            last_val = last.val.clone()
-                        } else {
-                            return Err(Error::new(ErrorKind::Other, "Unexpected '{' found"));
-                        }
-
-                        let mut min = String::new();
-                        let mut max = String::new();
-
-                        let mut min_done = false;
-
-                        while let Some(c) = char_iterator.next() {
-                            if c.is_digit(10) {
-                                if !min_done {
-                                    min.push(c);
-                                } else {
-                                    max.push(c);
-                                }
-                            } else if c == ',' {
-                                min_done = true;
-                                continue;
-                            } else if c == '}' {
-                                break;
-                            } else {
-                                return Err(Error::new(
-                                    ErrorKind::Other,
-                                    "Unexpected character found inside bracket",
-                                ));
-                            }
-                        }
-
-                        let min_usize = min.parse::<usize>();
-                        let max_usize = max.parse::<usize>();
-
-                        let final_min: Option<usize>;
-                        let final_max: Option<usize>;
-
-                        match min_usize {
-                            Ok(min_num) => {
-                                final_min = Some(min_num);
-                            }
-                            Err(_err) => {
-                                final_min = None;
-                            }
-                        }
-
-                        match max_usize {
-                            Ok(max_num) => {
-                                final_max = Some(max_num);
-                            }
-                            Err(_err) => {
-                                final_max = None;
-                            }
-                        }
-
-                        Some(RegexStep {
-                            rep: RegexRep::Range(final_min, final_max),
-                            val: last_val,
-                        })
-                    }
-                    '+' => {
-                        let last_val: RegexVal;
-                        if let Some(last) = steps.last_mut() {
-                            last_val = last.val.clone()
-                        } else {
-                            return Err(Error::new(ErrorKind::Other, "Unexpected '*' found"));
-                        }
-
-                        Some(RegexStep {
-                            rep: RegexRep::Any,
-                            val: last_val,
-                        })
-                    }
-                    '?' => {
-                        if let Some(last) = steps.last_mut() {
-                            last.rep = RegexRep::Optional;
-                        } else {
-                            return Err(Error::new(ErrorKind::Other, "Unexpected '?' found"));
-                        }
-                        None
-                    }
-                    '$' => match char_iterator.next() {
-                        None => {
-                            if let Some(last) = steps.last_mut() {
-                                last.rep = RegexRep::Last;
-                            } else {
-                                return Err(Error::new(ErrorKind::Other, "Unexpected '$' found"));
-                            }
-                            None
-                        }
-                        Some(_) => {
-                            return Err(Error::new(ErrorKind::Other, "Unexpected '$' found"))
-                        }
-                    },
-
-                    _ => {
-                        return Err(Error::new(
-                            ErrorKind::Other,
-                            "Unexpected character found (3)",
-                        ))
-                    }
-                };
+    pub fn new(expression: &str) -> Result<Self, Error> {
+        let ast = regex_ast::parse(expression)?;
+        let mut regex = Self::compile(expression, &Hir::from_ast(&ast))?;
+        regex.names = ast.capture_names();
+        Ok(regex)
+    }
 
-                if let Some(p) = step {
-                    //println!("added step {:?}", p);
-                    steps.push(p);
+    /// NEW_MANY: arma una sola expresion que matchea donde matchee cualquiera de los patrones.
+    /// Cada patron se lee por separado, asi un error se informa sobre el patron que lo tiene y
+    /// no sobre la union. Un patron vacio matchea en todos lados. Los grupos se numeran en cada
+    /// patron por separado; si dos patrones usan el mismo nombre, vale el del primero
+    pub fn new_many<P: AsRef<str>>(patterns: &[P]) -> Result<Self, PatternError> {
+        let mut hirs = vec![];
+        let mut names: Vec<(String, usize)> = vec![];
+        for (index, pattern) in patterns.iter().enumerate() {
+            let ast = regex_ast::parse(pattern.as_ref()).map_err(|error| PatternError {
+                index: Some(index),
+                error,
+            })?;
+            for (name, group) in ast.capture_names() {
+                if !names.iter().any(|(known, _)| *known == name) {
+                    names.push((name, group));
                 }
             }
-            expression_steps.push(steps);
+            hirs.push(Hir::from_ast(&ast));
         }
-        Ok(Regex { expression_steps })
+
+        let hir = match hirs.len() {
+            // Sin patrones no matchea nada: una clase vacia no acepta ningun caracter
+            0 => Hir::Class(HirClass::new(false, vec![], vec![])),
+            1 => hirs.remove(0),
+            _ => Hir::Alternation(hirs),
+        };
+        let mut regex =
+            Self::from_hir(&hir).map_err(|error| PatternError { index: None, error })?;
+        regex.names = names;
+        Ok(regex)
     }
 
-    pub fn test(self, value: &str) -> Result<bool, std::io::Error> {
-        if !value.is_ascii() {
-            return Err(Error::new(ErrorKind::Other, "The input is not ASCII"));
-        }
+    /// FROM_HIR: arma la expresion a partir de su representacion simplificada
+    pub fn from_hir(hir: &Hir) -> Result<Self, Error> {
+        Self::compile(&hir.to_string(), hir)
+    }
 
-        let mut final_result = false;
+    fn compile(expression: &str, hir: &Hir) -> Result<Self, Error> {
+        let expression_steps = hir.to_steps();
 
-        'main: for steps in self.expression_steps {
-            let mut queue = VecDeque::from(steps);
-            let mut stack: Vec<EvaluatedStep> = Vec::new();
-            let mut index = 0;
+        let program =
+            RegexProgram::compile(&expression_steps, hir.captures_len()).ok_or_else(|| {
+                Error::SizeLimit {
+                    pattern: expression.to_string(),
+                    span: Span::new(0, expression.len()),
+                    limit: SIZE_LIMIT,
+                }
+            })?;
+
+        Ok(Regex {
+            expression_steps,
+            program,
+            folded: None,
+            semantics: MatchSemantics::default(),
+            boundary: MatchBoundary::default(),
+            line_terminator: None,
+            names: vec![],
+        })
+    }
 
-            'steps: while let Some(step) = queue.pop_front() {
-                //println!("running step {:?}", step);
-                match step.rep {
-                    RegexRep::Exact(n) => {
-                        let mut match_size = 0;
-                        for _ in [1..n] {
-                            let size = step.val.matches(&value[index..]);
+    /// WITH_SEMANTICS: cambia la semantica con la que se elige el match (por defecto leftmost-first)
+    pub fn with_semantics(mut self, semantics: MatchSemantics) -> Self {
+        self.semantics = semantics;
+        self
+    }
 
-                            if size == 0 {
-                                match backtrack(step, &mut stack, &mut queue) {
-                                    Some(size) => {
-                                        index -= size;
-                                        continue 'steps;
-                                    }
-                                    None => continue 'main,
-                                }
-                            } else {
-                                match_size += size;
-                                index += size;
-                            }
-                        }
-                        stack.push(EvaluatedStep {
-                            step: step,
-                            match_size,
-                            backtrackable: false,
-                        })
-                    }
-                    RegexRep::Last => {
-                        let mut match_size = 0;
+    pub fn semantics(&self) -> MatchSemantics {
+        self.semantics
+    }
 
-                        let size = step.val.matches(&value[index..]);
+    /// WITH_BOUNDARY: exige que los matches sean palabras o lineas enteras. Si el primer match
+    /// posible no cumple, se prueban los mas cortos y los que empiezan despues
+    pub fn with_boundary(mut self, boundary: MatchBoundary) -> Self {
+        self.boundary = boundary;
+        self
+    }
 
-                        if size == 0 {
-                            continue 'main;
-                        } else {
-                            match_size += size;
-                            index += size;
+    pub fn boundary(&self) -> MatchBoundary {
+        self.boundary
+    }
 
-                            match value.chars().nth(index) {
-                                Some(_) => continue 'main,
-                                None => stack.push(EvaluatedStep {
-                                    step: step,
-                                    match_size,
-                                    backtrackable: false,
-                                }),
-                            }
-                        }
-                    }
-                    RegexRep::Optional => {
-                        let mut match_size = 0;
+    /// WITH_IGNORE_CASE: no distingue mayusculas de minusculas, ni en los literales ni en las
+    /// clases
+    pub fn with_ignore_case(mut self, ignore_case: bool) -> Self {
+        self.folded = ignore_case.then(|| self.program.fold_case());
+        self
+    }
 
-                        let size = step.val.matches(&value[index..]);
+    pub fn ignore_case(&self) -> bool {
+        self.folded.is_some()
+    }
 
-                        if size != 0 {
-                            match_size += size;
-                            index += size;
-                        }
+    /// WITH_LINE_TERMINATOR: ningun match consume `terminator`, ni con '.' ni con clases que lo
+    /// acepten, asi los matches nunca abarcan mas de una linea y la busqueda de cada uno termina
+    /// al final de su linea en lugar de seguir hasta el final del texto
+    pub fn with_line_terminator(mut self, terminator: Option<u8>) -> Self {
+        self.line_terminator = terminator;
+        self
+    }
 
-                        stack.push(EvaluatedStep {
-                            step: step,
-                            match_size,
-                            backtrackable: false,
-                        })
-                    }
-                    RegexRep::Any => {
-                        let mut keep_matching = true;
-                        while keep_matching {
-                            let match_size = step.val.matches(&value[index..]);
-                            if match_size != 0 {
-                                index += match_size;
-                                stack.push(EvaluatedStep {
-                                    step: step.clone(),
-                                    match_size,
-                                    backtrackable: true,
-                                })
-                            } else {
-                                keep_matching = false;
-                            }
-                        }
-                    }
-                    RegexRep::Range(min, max) => {
-                        let mut keep_matching = true;
-                        let mut match_counter = 1; // arranco con 1 porque ya conte el caracter anterior
-                        while keep_matching {
-                            let match_size = step.val.matches(&value[index..]);
-                            if match_size != 0 {
-                                match_counter += 1;
-                                index += match_size;
-                                stack.push(EvaluatedStep {
-                                    step: step.clone(),
-                                    match_size,
-                                    backtrackable: false,
-                                })
-                            } else {
-                                keep_matching = false;
-                            }
-                        }
+    pub fn line_terminator(&self) -> Option<u8> {
+        self.line_terminator
+    }
 
-                        let matches_range: bool;
+    /// STEPS: devuelve los pasos de cada alternativa de la expresion
+    pub fn steps(&self) -> &[Vec<RegexStep>] {
+        &self.expression_steps
+    }
 
-                        match (min, max) {
-                            (Some(min_val), Some(max_val)) => {
-                                matches_range = match_counter >= min_val as i32
-                                    && match_counter <= max_val as i32
-                            }
-                            (Some(min_val), None) => {
-                                matches_range = match_counter >= min_val as i32
-                            }
-                            (None, Some(max_val)) => {
-                                matches_range = match_counter <= max_val as i32
-                            }
-                            (None, None) => matches_range = false,
-                        }
+    pub fn test(&self, value: &str) -> Result<bool, std::io::Error> {
+        Ok(self.is_match(value.as_bytes()))
+    }
+}
 
-                        if !matches_range {
-                            continue 'main;
-                        }
-                    }
-                }
-            }
-            final_result = true;
-        }
+impl Matcher for Regex {
+    fn find_at(&self, haystack: &[u8], start: usize) -> Option<Match> {
+        self.captures_at(haystack, start)
+            .and_then(|captures| captures.get(0))
+    }
 
-        Ok(final_result)
+    fn captures_at(&self, haystack: &[u8], start: usize) -> Option<Captures> {
+        self.folded
+            .as_ref()
+            .unwrap_or(&self.program)
+            .search(
+                haystack,
+                start,
+                self.semantics,
+                self.boundary,
+                self.line_terminator,
+            )
+            .map(Captures::new)
     }
-}
 
-fn backtrack(
-    current: RegexStep,
-    evaluated: &mut Vec<EvaluatedStep>,
-    next: &mut VecDeque<RegexStep>,
-) -> Option<usize> {
-    let mut back_size = 0;
-    next.push_front(current);
-    while let Some(e) = evaluated.pop() {
-        back_size += e.match_size;
+    fn captures_len(&self) -> usize {
+        self.program.slots() / 2
+    }
 
-        if e.backtrackable {
-            return Some(back_size);
-        } else {
-            next.push_front(e.step);
-        }
+    fn capture_index(&self, name: &str) -> Option<usize> {
+        self.names
+            .iter()
+            .find(|(known, _)| known == name)
+            .map(|(_, index)| *index)
     }
-    None
 }
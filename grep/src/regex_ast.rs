@@ -0,0 +1,536 @@
+use crate::regex_class::RegexClass;
+use crate::regex_error::{Error, Span};
+use std::fmt;
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+/// Arbol sintactico de una expresion. Conserva la sintaxis original (por ejemplo "+" y "{1,}"
+/// son nodos distintos) y la posicion de cada nodo dentro del patron
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ast {
+    Empty(Span),
+    Literal {
+        span: Span,
+        c: char,
+        escaped: bool, // Si estaba escrito como "\c"
+    },
+    Dot(Span),
+    LineStart(Span),
+    LineEnd(Span),
+    Bracket {
+        span: Span,
+        negated: bool,
+        items: Vec<BracketItem>,
+    },
+    Group {
+        span: Span,
+        index: Option<usize>, // None para los grupos "(?:...)" que no capturan
+        name: Option<String>, // Para los grupos "(?P<nombre>...)" o "(?<nombre>...)"
+        ast: Box<Ast>,
+    },
+    Repetition {
+        span: Span,
+        op: RepetitionOp,
+        ast: Box<Ast>,
+    },
+    Concat {
+        span: Span,
+        asts: Vec<Ast>,
+    },
+    Alternation {
+        span: Span,
+        asts: Vec<Ast>,
+    },
+}
+
+/// Elemento dentro de un "[...]"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BracketItem {
+    Char(char),
+    Range(char, char),
+    Class(RegexClass),
+}
+
+/// Operador de repeticion tal como estaba escrito
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepetitionOp {
+    Star,                                // *
+    Plus,                                // +
+    Question,                            // ?
+    Exact(usize),                        // {n}
+    Range(Option<usize>, Option<usize>), // {n,m}
+}
+
+impl Ast {
+    /// SPAN: devuelve la porcion del patron que ocupa el nodo
+    pub fn span(&self) -> Span {
+        match self {
+            Ast::Empty(span) | Ast::Dot(span) | Ast::LineStart(span) | Ast::LineEnd(span) => *span,
+            Ast::Literal { span, .. }
+            | Ast::Bracket { span, .. }
+            | Ast::Group { span, .. }
+            | Ast::Repetition { span, .. }
+            | Ast::Concat { span, .. }
+            | Ast::Alternation { span, .. } => *span,
+        }
+    }
+
+    /// CAPTURE_NAMES: devuelve el nombre y el numero de cada grupo con nombre
+    pub fn capture_names(&self) -> Vec<(String, usize)> {
+        let mut names = vec![];
+        self.collect_names(&mut names);
+        names
+    }
+
+    fn collect_names(&self, names: &mut Vec<(String, usize)>) {
+        match self {
+            Ast::Group {
+                index, name, ast, ..
+            } => {
+                if let (Some(index), Some(name)) = (index, name) {
+                    names.push((name.clone(), *index));
+                }
+                ast.collect_names(names);
+            }
+            Ast::Repetition { ast, .. } => ast.collect_names(names),
+            Ast::Concat { asts, .. } | Ast::Alternation { asts, .. } => {
+                asts.iter().for_each(|ast| ast.collect_names(names))
+            }
+            _ => {}
+        }
+    }
+}
+
+/// PARSE: lee el patron y devuelve su arbol sintactico
+pub fn parse(pattern: &str) -> Result<Ast, Error> {
+    let mut parser = Parser {
+        pattern,
+        chars: pattern.char_indices().peekable(),
+        groups: 0,
+        names: vec![],
+    };
+
+    let ast = parser.parse_alternatives()?;
+
+    // Lo unico que puede cortar las alternativas antes del final es un ')' de mas
+    if let Some((start, _)) = parser.chars.next() {
+        let (pattern, span) = parser.located(start, start + 1);
+        return Err(Error::UnbalancedParen { pattern, span });
+    }
+
+    Ok(ast)
+}
+
+impl fmt::Display for Ast {
+    /// Vuelve a escribir el patron tal como fue leido
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Ast::Empty(_) => Ok(()),
+            Ast::Literal { c, escaped, .. } => {
+                if *escaped {
+                    write!(f, "\\{}", c)
+                } else {
+                    write!(f, "{}", c)
+                }
+            }
+            Ast::Dot(_) => write!(f, "."),
+            Ast::LineStart(_) => write!(f, "^"),
+            Ast::LineEnd(_) => write!(f, "$"),
+            Ast::Bracket { negated, items, .. } => {
+                write!(f, "[")?;
+                if *negated {
+                    write!(f, "^")?;
+                }
+                for item in items {
+                    match item {
+                        BracketItem::Char(c) => write!(f, "{}", c)?,
+                        BracketItem::Range(from, to) => write!(f, "{}-{}", from, to)?,
+                        BracketItem::Class(class_type) => write!(f, "[:{}:]", class_type.name())?,
+                    }
+                }
+                write!(f, "]")
+            }
+            Ast::Group {
+                index, name, ast, ..
+            } => match (index, name) {
+                (Some(_), Some(name)) => write!(f, "(?P<{}>{})", name, ast),
+                (Some(_), None) => write!(f, "({})", ast),
+                (None, _) => write!(f, "(?:{})", ast),
+            },
+            Ast::Repetition { op, ast, .. } => {
+                write!(f, "{}", ast)?;
+                match op {
+                    RepetitionOp::Star => write!(f, "*"),
+                    RepetitionOp::Plus => write!(f, "+"),
+                    RepetitionOp::Question => write!(f, "?"),
+                    RepetitionOp::Exact(n) => write!(f, "{{{}}}", n),
+                    RepetitionOp::Range(min, max) => {
+                        write!(f, "{{")?;
+                        if let Some(min) = min {
+                            write!(f, "{}", min)?;
+                        }
+                        write!(f, ",")?;
+                        if let Some(max) = max {
+                            write!(f, "{}", max)?;
+                        }
+                        write!(f, "}}")
+                    }
+                }
+            }
+            Ast::Concat { asts, .. } => asts.iter().try_for_each(|ast| write!(f, "{}", ast)),
+            Ast::Alternation { asts, .. } => {
+                for (i, ast) in asts.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "|")?;
+                    }
+                    write!(f, "{}", ast)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    pattern: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    groups: usize,
+    names: Vec<String>, // Nombres de grupos ya usados, no se pueden repetir
+}
+
+impl Parser<'_> {
+    /// POS: posicion (en bytes) del proximo caracter a leer
+    fn pos(&mut self) -> usize {
+        match self.chars.peek() {
+            Some((i, _)) => *i,
+            None => self.pattern.len(),
+        }
+    }
+
+    /// LOCATED: arma los datos comunes a todos los errores
+    fn located(&self, start: usize, end: usize) -> (String, Span) {
+        (self.pattern.to_string(), Span::new(start, end))
+    }
+
+    /// PARSE_ALTERNATIVES: lee alternativas separadas por '|' hasta el final o hasta un ')'
+    fn parse_alternatives(&mut self) -> Result<Ast, Error> {
+        let start = self.pos();
+        let mut asts = vec![self.parse_concat()?];
+
+        while let Some((_, '|')) = self.chars.peek() {
+            self.chars.next();
+            asts.push(self.parse_concat()?);
+        }
+
+        if asts.len() == 1 {
+            return Ok(asts.remove(0));
+        }
+
+        let end = self.pos();
+        Ok(Ast::Alternation {
+            span: Span::new(start, end),
+            asts,
+        })
+    }
+
+    fn parse_concat(&mut self) -> Result<Ast, Error> {
+        let concat_start = self.pos();
+        let mut asts: Vec<Ast> = vec![];
+
+        // Recorremos la expression char por char con .next()
+        while let Some(&(start, c)) = self.chars.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            self.chars.next();
+
+            let ast = match c {
+                '.' => Ast::Dot(Span::new(start, start + 1)),
+                '^' => Ast::LineStart(Span::new(start, start + 1)),
+                '$' => Ast::LineEnd(Span::new(start, start + 1)),
+                '\\' => match self.chars.next() {
+                    Some((i, literal)) => Ast::Literal {
+                        span: Span::new(start, i + literal.len_utf8()),
+                        c: literal,
+                        escaped: true,
+                    },
+                    None => {
+                        let (pattern, span) = self.located(start, start + 1);
+                        return Err(Error::TrailingBackslash { pattern, span });
+                    }
+                },
+                '[' => self.parse_bracket(start)?,
+                '(' => self.parse_group(start)?,
+                '*' => {
+                    self.repeat_last(&mut asts, RepetitionOp::Star, start)?;
+                    continue;
+                }
+                '+' => {
+                    self.repeat_last(&mut asts, RepetitionOp::Plus, start)?;
+                    continue;
+                }
+                '?' => {
+                    self.repeat_last(&mut asts, RepetitionOp::Question, start)?;
+                    continue;
+                }
+                '{' => {
+                    let op = self.parse_braces(start)?;
+                    self.repeat_last(&mut asts, op, start)?;
+                    continue;
+                }
+                _ => Ast::Literal {
+                    span: Span::new(start, start + c.len_utf8()),
+                    c,
+                    escaped: false,
+                },
+            };
+
+            asts.push(ast);
+        }
+
+        let end = self.pos();
+        let span = Span::new(concat_start, end);
+        match asts.len() {
+            0 => Ok(Ast::Empty(span)),
+            1 => Ok(asts.remove(0)),
+            _ => Ok(Ast::Concat { span, asts }),
+        }
+    }
+
+    /// PARSE_GROUP: lee un "(...)", "(?:...)", "(?P<nombre>...)" o "(?<nombre>...)" a partir del
+    /// '(' que esta en `start` (ya consumido)
+    fn parse_group(&mut self, start: usize) -> Result<Ast, Error> {
+        let mut lookahead = self.chars.clone();
+        let (index, name) = match (lookahead.next(), lookahead.next(), lookahead.next()) {
+            (Some((_, '?')), Some((_, ':')), _) => {
+                self.chars.next();
+                self.chars.next();
+                (None, None)
+            }
+            (Some((_, '?')), Some((_, 'P')), Some((_, '<'))) => {
+                self.chars.nth(2);
+                self.groups += 1;
+                (Some(self.groups), Some(self.parse_group_name(start)?))
+            }
+            (Some((_, '?')), Some((_, '<')), _) => {
+                self.chars.nth(1);
+                self.groups += 1;
+                (Some(self.groups), Some(self.parse_group_name(start)?))
+            }
+            _ => {
+                self.groups += 1;
+                (Some(self.groups), None)
+            }
+        };
+
+        let ast = self.parse_alternatives()?;
+
+        match self.chars.next() {
+            Some((i, ')')) => Ok(Ast::Group {
+                span: Span::new(start, i + 1),
+                index,
+                name,
+                ast: Box::new(ast),
+            }),
+            _ => {
+                let (pattern, span) = self.located(start, start + 1);
+                Err(Error::UnbalancedParen { pattern, span })
+            }
+        }
+    }
+
+    /// PARSE_GROUP_NAME: lee el nombre de un grupo hasta el '>' (ya consumido el '<'). El nombre
+    /// tiene letras, digitos y '_', no empieza con un digito y no se repite en el patron
+    fn parse_group_name(&mut self, start: usize) -> Result<String, Error> {
+        let name_start = self.pos();
+        let mut name = String::new();
+        let end = loop {
+            match self.chars.next() {
+                Some((i, '>')) => break i,
+                Some((_, c)) => name.push(c),
+                None => {
+                    let (pattern, span) = self.located(start, start + 1);
+                    return Err(Error::UnbalancedParen { pattern, span });
+                }
+            }
+        };
+
+        let valid = name.chars().all(|c| c.is_alphanumeric() || c == '_')
+            && name.chars().next().is_some_and(|c| !c.is_ascii_digit());
+        if !valid {
+            let (pattern, span) = self.located(name_start, end);
+            return Err(Error::InvalidGroupName { pattern, span });
+        }
+        if self.names.contains(&name) {
+            let (pattern, span) = self.located(name_start, end);
+            return Err(Error::DuplicateGroupName { pattern, span });
+        }
+        self.names.push(name.clone());
+        Ok(name)
+    }
+
+    /// PARSE_BRACKET: lee un "[...]" a partir del '[' que esta en `start` (ya consumido).
+    /// Adentro no hay escapes: un ']' al principio y un '-' al principio o al final son literales
+    fn parse_bracket(&mut self, start: usize) -> Result<Ast, Error> {
+        let mut items: Vec<BracketItem> = vec![];
+        let mut negated = false;
+
+        if let Some((_, '^')) = self.chars.peek() {
+            self.chars.next();
+            negated = true;
+        }
+
+        let mut first = true;
+        loop {
+            let (item_start, c) = match self.chars.next() {
+                Some((_, ']')) if !first => break,
+                Some(next) => next,
+                None => {
+                    let (pattern, span) = self.located(start, start + 1);
+                    return Err(Error::UnclosedBracket { pattern, span });
+                }
+            };
+            first = false;
+
+            if c == '[' {
+                if let Some((_, ':')) = self.chars.peek() {
+                    items.push(BracketItem::Class(self.parse_class(start, item_start)?));
+                    continue;
+                }
+            }
+
+            // "c-d" es un rango salvo que el '-' sea el ultimo caracter antes del ']'
+            let mut lookahead = self.chars.clone();
+            match (lookahead.next(), lookahead.next()) {
+                (Some((_, '-')), Some((end_start, end))) if end != ']' => {
+                    self.chars.next();
+                    self.chars.next();
+                    if end < c {
+                        let span_end = end_start + end.len_utf8();
+                        let (pattern, span) = self.located(item_start, span_end);
+                        return Err(Error::InvalidRange { pattern, span });
+                    }
+                    items.push(BracketItem::Range(c, end));
+                }
+                _ => items.push(BracketItem::Char(c)),
+            }
+        }
+
+        let end = self.pos();
+        Ok(Ast::Bracket {
+            span: Span::new(start, end),
+            negated,
+            items,
+        })
+    }
+
+    /// PARSE_CLASS: lee el resto de una clase "[:nombre:]" cuyo '[' esta en `class_start`
+    fn parse_class(&mut self, start: usize, class_start: usize) -> Result<RegexClass, Error> {
+        let mut name = String::new();
+        let mut closed = false;
+        while let Some((_, c)) = self.chars.next() {
+            name.push(c);
+            if name.len() > 1 && name.ends_with(':') {
+                if let Some((_, ']')) = self.chars.peek() {
+                    self.chars.next();
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        if !closed {
+            let (pattern, span) = self.located(start, start + 1);
+            return Err(Error::UnclosedBracket { pattern, span });
+        }
+
+        // name es ":nombre:"
+        match RegexClass::from_name(&name[1..name.len() - 1]) {
+            Some(class_type) => Ok(class_type),
+            None => {
+                let end = self.pos();
+                let (pattern, span) = self.located(class_start, end);
+                Err(Error::InvalidClassName { pattern, span })
+            }
+        }
+    }
+
+    /// PARSE_BRACES: lee una repeticion de la forma "{n}", "{n,}", "{,m}" o "{n,m}"
+    fn parse_braces(&mut self, start: usize) -> Result<RepetitionOp, Error> {
+        let mut min = String::new();
+        let mut max = String::new();
+        let mut min_done = false;
+
+        loop {
+            match self.chars.next() {
+                Some((_, c)) if c.is_ascii_digit() => {
+                    if !min_done {
+                        min.push(c);
+                    } else {
+                        max.push(c);
+                    }
+                }
+                Some((_, ',')) if !min_done => min_done = true,
+                Some((_, '}')) => break,
+                _ => {
+                    let end = self.pos();
+                    let (pattern, span) = self.located(start, end);
+                    return Err(Error::BadRepetition { pattern, span });
+                }
+            }
+        }
+
+        let end = self.pos();
+        let (pattern, span) = self.located(start, end);
+        let bad_repetition = Error::BadRepetition { pattern, span };
+
+        let final_min = match min.as_str() {
+            "" => None,
+            _ => Some(min.parse::<usize>().map_err(|_| bad_repetition.clone())?),
+        };
+        let final_max = match max.as_str() {
+            "" => None,
+            _ => Some(max.parse::<usize>().map_err(|_| bad_repetition.clone())?),
+        };
+
+        if !min_done {
+            return match final_min {
+                Some(n) => Ok(RepetitionOp::Exact(n)),
+                None => Err(bad_repetition),
+            };
+        }
+
+        if let (Some(min_val), Some(max_val)) = (final_min, final_max) {
+            if min_val > max_val {
+                return Err(bad_repetition);
+            }
+        }
+
+        Ok(RepetitionOp::Range(final_min, final_max))
+    }
+
+    /// REPEAT_LAST: aplica la repeticion al ultimo nodo leido
+    fn repeat_last(
+        &mut self,
+        asts: &mut Vec<Ast>,
+        op: RepetitionOp,
+        start: usize,
+    ) -> Result<(), Error> {
+        let end = self.pos();
+        let last = match asts.pop() {
+            Some(last) if !matches!(last, Ast::LineStart(_) | Ast::LineEnd(_)) => last,
+            _ => {
+                let (pattern, span) = self.located(start, end);
+                return Err(Error::NothingToRepeat { pattern, span });
+            }
+        };
+
+        asts.push(Ast::Repetition {
+            span: Span::new(last.span().start, end),
+            op,
+            ast: Box::new(last),
+        });
+
+        Ok(())
+    }
+}
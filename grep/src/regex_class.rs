@@ -1,4 +1,4 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum RegexClass {
     Alphanumeric,
     Alphabetic,
@@ -8,3 +8,44 @@ pub enum RegexClass {
     Whitespace,
     Punctuation,
 }
+
+impl RegexClass {
+    /// FROM_NAME: devuelve la clase correspondiente a un nombre POSIX como "alpha"
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "alnum" => Some(RegexClass::Alphanumeric),
+            "alpha" => Some(RegexClass::Alphabetic),
+            "digit" => Some(RegexClass::Digit),
+            "lower" => Some(RegexClass::Lowercase),
+            "upper" => Some(RegexClass::Uppercase),
+            "space" => Some(RegexClass::Whitespace),
+            "punct" => Some(RegexClass::Punctuation),
+            _ => None,
+        }
+    }
+
+    /// NAME: devuelve el nombre POSIX de la clase
+    pub fn name(&self) -> &'static str {
+        match self {
+            RegexClass::Alphanumeric => "alnum",
+            RegexClass::Alphabetic => "alpha",
+            RegexClass::Digit => "digit",
+            RegexClass::Lowercase => "lower",
+            RegexClass::Uppercase => "upper",
+            RegexClass::Whitespace => "space",
+            RegexClass::Punctuation => "punct",
+        }
+    }
+
+    pub fn matches(&self, c: char) -> bool {
+        match self {
+            RegexClass::Alphanumeric => c.is_alphanumeric(),
+            RegexClass::Alphabetic => c.is_alphabetic(),
+            RegexClass::Digit => c.is_ascii_digit(),
+            RegexClass::Lowercase => c.is_lowercase(),
+            RegexClass::Uppercase => c.is_uppercase(),
+            RegexClass::Whitespace => c.is_whitespace(),
+            RegexClass::Punctuation => c.is_ascii_punctuation(),
+        }
+    }
+}
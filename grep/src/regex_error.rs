@@ -0,0 +1,136 @@
+use std::fmt;
+
+/// Porcion de la expresion (en bytes) donde se encontro un error
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+}
+
+/// Errores que puede devolver `Regex::new` al leer una expresion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Un '[' sin su ']'
+    UnclosedBracket { pattern: String, span: Span },
+    /// Un '(' sin su ')' o un ')' sin su '('
+    UnbalancedParen { pattern: String, span: Span },
+    /// Una clase "[:nombre:]" que no existe
+    InvalidClassName { pattern: String, span: Span },
+    /// Un rango "a-z" cuyo final es menor que su principio
+    InvalidRange { pattern: String, span: Span },
+    /// Una repeticion "{n,m}" mal escrita o con n > m
+    BadRepetition { pattern: String, span: Span },
+    /// Un '*', '+', '?' o '{' sin nada antes para repetir
+    NothingToRepeat { pattern: String, span: Span },
+    /// Un nombre de grupo "(?P<nombre>...)" vacio o con caracteres invalidos
+    InvalidGroupName { pattern: String, span: Span },
+    /// Dos grupos con el mismo nombre
+    DuplicateGroupName { pattern: String, span: Span },
+    /// Un '\' al final de la expresion
+    TrailingBackslash { pattern: String, span: Span },
+    /// La expresion compilada supera la cantidad maxima de instrucciones
+    SizeLimit {
+        pattern: String,
+        span: Span,
+        limit: usize,
+    },
+}
+
+impl Error {
+    /// PATTERN: devuelve la expresion que produjo el error
+    pub fn pattern(&self) -> &str {
+        match self {
+            Error::UnclosedBracket { pattern, .. }
+            | Error::UnbalancedParen { pattern, .. }
+            | Error::InvalidClassName { pattern, .. }
+            | Error::InvalidRange { pattern, .. }
+            | Error::BadRepetition { pattern, .. }
+            | Error::NothingToRepeat { pattern, .. }
+            | Error::InvalidGroupName { pattern, .. }
+            | Error::DuplicateGroupName { pattern, .. }
+            | Error::TrailingBackslash { pattern, .. }
+            | Error::SizeLimit { pattern, .. } => pattern,
+        }
+    }
+
+    /// SPAN: devuelve la porcion de la expresion donde esta el error
+    pub fn span(&self) -> Span {
+        match self {
+            Error::UnclosedBracket { span, .. }
+            | Error::UnbalancedParen { span, .. }
+            | Error::InvalidClassName { span, .. }
+            | Error::InvalidRange { span, .. }
+            | Error::BadRepetition { span, .. }
+            | Error::NothingToRepeat { span, .. }
+            | Error::InvalidGroupName { span, .. }
+            | Error::DuplicateGroupName { span, .. }
+            | Error::TrailingBackslash { span, .. }
+            | Error::SizeLimit { span, .. } => *span,
+        }
+    }
+
+    /// DESCRIPTION: describe el error en una linea, sin la expresion
+    pub fn description(&self) -> String {
+        let span = self.span();
+        let text = self.pattern().get(span.start..span.end).unwrap_or("");
+        match self {
+            Error::UnclosedBracket { .. } => "unclosed bracket".to_string(),
+            Error::UnbalancedParen { .. } => "unbalanced parenthesis".to_string(),
+            Error::InvalidClassName { .. } => format!("invalid character class '{}'", text),
+            Error::InvalidRange { .. } => format!("invalid range '{}'", text),
+            Error::BadRepetition { .. } => format!("invalid repetition '{}'", text),
+            Error::NothingToRepeat { .. } => {
+                format!("repetition operator '{}' has nothing to repeat", text)
+            }
+            Error::InvalidGroupName { .. } => format!("invalid capture group name '{}'", text),
+            Error::DuplicateGroupName { .. } => format!("duplicate capture group name '{}'", text),
+            Error::TrailingBackslash { .. } => "trailing backslash".to_string(),
+            Error::SizeLimit { limit, .. } => {
+                format!(
+                    "compiled pattern exceeds the size limit of {} instructions",
+                    limit
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    /// Muestra la expresion y marca con '^' la parte donde esta el error
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pattern = self.pattern();
+        let span = self.span();
+
+        // Las columnas se cuentan en caracteres para que el '^' quede alineado
+        let column = pattern[..span.start].chars().count();
+        let width = pattern[span.start..span.end].chars().count().max(1);
+
+        writeln!(f, "regex parse error:")?;
+        writeln!(f, "    {}", pattern)?;
+        writeln!(f, "    {}{}", " ".repeat(column), "^".repeat(width))?;
+        write!(f, "error: {}", self.description())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Error de uno de los patrones de `Regex::new_many`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternError {
+    pub index: Option<usize>, // Posicion del patron en la lista; None si es de la union
+    pub error: Error,
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.error)
+    }
+}
+
+impl std::error::Error for PatternError {}
@@ -0,0 +1,358 @@
+use crate::regex_ast::{Ast, BracketItem, RepetitionOp};
+use crate::regex_class::RegexClass;
+use crate::regex_rep::RegexRep;
+use crate::regex_step::RegexStep;
+use crate::regex_val::RegexVal;
+use std::fmt;
+
+/// Representacion simplificada de una expresion: las clases estan normalizadas (rangos ordenados
+/// y unidos), toda repeticion es un rango {min,max} y no quedan rastros de la sintaxis original
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Hir {
+    Empty,
+    Literal(char),
+    Any,
+    Class(HirClass),
+    LineStart,
+    LineEnd,
+    Group {
+        index: Option<usize>, // None si no captura
+        hir: Box<Hir>,
+    },
+    Repetition {
+        min: usize,
+        max: Option<usize>,
+        hir: Box<Hir>,
+    },
+    Concat(Vec<Hir>),
+    Alternation(Vec<Hir>),
+}
+
+/// Conjunto de caracteres normalizado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HirClass {
+    pub negated: bool,
+    pub ranges: Vec<(char, char)>, // Ordenados y sin superponerse
+    pub classes: Vec<RegexClass>,  // Ordenadas y sin repetidos
+}
+
+impl HirClass {
+    pub fn new(negated: bool, mut ranges: Vec<(char, char)>, mut classes: Vec<RegexClass>) -> Self {
+        ranges.sort();
+        let mut merged: Vec<(char, char)> = vec![];
+        for (from, to) in ranges {
+            match merged.last_mut() {
+                Some(last) if (last.1 as u32).saturating_add(1) >= from as u32 => {
+                    last.1 = last.1.max(to);
+                }
+                _ => merged.push((from, to)),
+            }
+        }
+
+        classes.sort();
+        classes.dedup();
+
+        HirClass {
+            negated,
+            ranges: merged,
+            classes,
+        }
+    }
+
+    /// TO_VAL: elige el valor mas simple que representa a la clase
+    fn to_val(&self) -> RegexVal {
+        let single_chars = self.ranges.iter().all(|(from, to)| from == to);
+
+        if self.classes.is_empty() && single_chars {
+            let chars = self.ranges.iter().map(|(c, _)| *c).collect();
+            if self.negated {
+                RegexVal::NotAllowed(chars)
+            } else {
+                RegexVal::Allowed(chars)
+            }
+        } else if self.ranges.is_empty() && self.classes.len() == 1 && !self.negated {
+            RegexVal::Class(self.classes[0])
+        } else {
+            RegexVal::Set {
+                negated: self.negated,
+                ranges: self.ranges.clone(),
+                classes: self.classes.clone(),
+            }
+        }
+    }
+}
+
+impl Hir {
+    /// FROM_AST: simplifica el arbol sintactico
+    pub fn from_ast(ast: &Ast) -> Hir {
+        match ast {
+            Ast::Empty(_) => Hir::Empty,
+            Ast::Literal { c, .. } => Hir::Literal(*c),
+            Ast::Dot(_) => Hir::Any,
+            Ast::LineStart(_) => Hir::LineStart,
+            Ast::LineEnd(_) => Hir::LineEnd,
+            Ast::Bracket { negated, items, .. } => {
+                let mut ranges = vec![];
+                let mut classes = vec![];
+                for item in items {
+                    match item {
+                        BracketItem::Char(c) => ranges.push((*c, *c)),
+                        BracketItem::Range(from, to) => ranges.push((*from, *to)),
+                        BracketItem::Class(class_type) => classes.push(*class_type),
+                    }
+                }
+
+                let class = HirClass::new(*negated, ranges, classes);
+                match (class.negated, class.ranges.as_slice(), class.classes.len()) {
+                    (false, [(from, to)], 0) if from == to => Hir::Literal(*from),
+                    _ => Hir::Class(class),
+                }
+            }
+            Ast::Group { index, ast, .. } => Hir::Group {
+                index: *index,
+                hir: Box::new(Hir::from_ast(ast)),
+            },
+            Ast::Repetition { op, ast, .. } => {
+                let (min, max) = match *op {
+                    RepetitionOp::Star => (0, None),
+                    RepetitionOp::Plus => (1, None),
+                    RepetitionOp::Question => (0, Some(1)),
+                    RepetitionOp::Exact(n) => (n, Some(n)),
+                    RepetitionOp::Range(min, max) => (min.unwrap_or(0), max),
+                };
+                Hir::repetition(min, max, Hir::from_ast(ast))
+            }
+            Ast::Concat { asts, .. } => Hir::concat(asts.iter().map(Hir::from_ast).collect()),
+            Ast::Alternation { asts, .. } => {
+                Hir::Alternation(asts.iter().map(Hir::from_ast).collect())
+            }
+        }
+    }
+
+    /// REPETITION: arma una repeticion, evitando las que no hacen nada
+    pub fn repetition(min: usize, max: Option<usize>, hir: Hir) -> Hir {
+        match (min, max) {
+            (1, Some(1)) => hir,
+            _ => Hir::Repetition {
+                min,
+                max,
+                hir: Box::new(hir),
+            },
+        }
+    }
+
+    /// CONCAT: arma una concatenacion aplanando las que esten anidadas
+    pub fn concat(hirs: Vec<Hir>) -> Hir {
+        let mut flat = vec![];
+        for hir in hirs {
+            match hir {
+                Hir::Concat(inner) => flat.extend(inner),
+                Hir::Empty => {}
+                _ => flat.push(hir),
+            }
+        }
+
+        match flat.len() {
+            0 => Hir::Empty,
+            1 => flat.remove(0),
+            _ => Hir::Concat(flat),
+        }
+    }
+
+    /// CAPTURES_LEN: cantidad de grupos de captura (el mayor indice usado)
+    pub fn captures_len(&self) -> usize {
+        match self {
+            Hir::Group { index, hir } => index.unwrap_or(0).max(hir.captures_len()),
+            Hir::Repetition { hir, .. } => hir.captures_len(),
+            Hir::Concat(hirs) | Hir::Alternation(hirs) => {
+                hirs.iter().map(Hir::captures_len).max().unwrap_or(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// TO_STEPS: traduce la expresion a los pasos que ejecuta `Regex`
+    pub fn to_steps(&self) -> Vec<Vec<RegexStep>> {
+        match self {
+            Hir::Alternation(hirs) => hirs.iter().map(Hir::to_concat_steps).collect(),
+            _ => vec![self.to_concat_steps()],
+        }
+    }
+
+    fn to_concat_steps(&self) -> Vec<RegexStep> {
+        match self {
+            Hir::Empty => vec![],
+            Hir::Concat(hirs) => hirs.iter().map(Hir::to_step).collect(),
+            _ => vec![self.to_step()],
+        }
+    }
+
+    fn to_step(&self) -> RegexStep {
+        match self {
+            Hir::Repetition { min, max, hir } => {
+                let rep = match (*min, *max) {
+                    (0, None) => RegexRep::Any,
+                    (0, Some(1)) => RegexRep::Optional,
+                    (min, Some(max)) if min == max => RegexRep::Exact(min),
+                    (min, max) => RegexRep::Range(Some(min), max),
+                };
+                RegexStep {
+                    val: hir.to_val(),
+                    rep,
+                }
+            }
+            _ => RegexStep {
+                val: self.to_val(),
+                rep: RegexRep::Exact(1),
+            },
+        }
+    }
+
+    fn to_val(&self) -> RegexVal {
+        match self {
+            Hir::Literal(c) => RegexVal::Literal(*c),
+            Hir::Any => RegexVal::Wildcard,
+            Hir::Class(class) => class.to_val(),
+            Hir::LineStart => RegexVal::LineStart,
+            Hir::LineEnd => RegexVal::LineEnd,
+            Hir::Group { index, hir } => RegexVal::Group(*index, hir.to_steps()),
+            // El resto se agrupa sin capturar para poder repetirlo
+            _ => RegexVal::Group(None, self.to_steps()),
+        }
+    }
+}
+
+impl fmt::Display for Hir {
+    /// Escribe un patron equivalente, usando la forma mas corta de cada repeticion
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Hir::Empty => Ok(()),
+            Hir::Literal(c) => {
+                if "\\.[](){}*+?|^$".contains(*c) {
+                    write!(f, "\\{}", c)
+                } else {
+                    write!(f, "{}", c)
+                }
+            }
+            Hir::Any => write!(f, "."),
+            Hir::Class(class) => write!(f, "{}", class),
+            Hir::LineStart => write!(f, "^"),
+            Hir::LineEnd => write!(f, "$"),
+            Hir::Group { index, hir } => match index {
+                Some(_) => write!(f, "({})", hir),
+                None => write!(f, "(?:{})", hir),
+            },
+            Hir::Repetition { min, max, hir } => {
+                // "^*" no es valido: las anclas tambien se repiten dentro de un grupo
+                match hir.as_ref() {
+                    Hir::Empty
+                    | Hir::Concat(_)
+                    | Hir::Alternation(_)
+                    | Hir::LineStart
+                    | Hir::LineEnd => write!(f, "(?:{})", hir)?,
+                    _ => write!(f, "{}", hir)?,
+                }
+                match (*min, *max) {
+                    (0, None) => write!(f, "*"),
+                    (1, None) => write!(f, "+"),
+                    (0, Some(1)) => write!(f, "?"),
+                    (min, Some(max)) if min == max => write!(f, "{{{}}}", min),
+                    (min, Some(max)) => write!(f, "{{{},{}}}", min, max),
+                    (min, None) => write!(f, "{{{},}}", min),
+                }
+            }
+            Hir::Concat(hirs) => {
+                for hir in hirs {
+                    match hir {
+                        Hir::Alternation(_) => write!(f, "(?:{})", hir)?,
+                        _ => write!(f, "{}", hir)?,
+                    }
+                }
+                Ok(())
+            }
+            Hir::Alternation(hirs) => {
+                for (i, hir) in hirs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "|")?;
+                    }
+                    write!(f, "{}", hir)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl fmt::Display for HirClass {
+    /// Ubica ']', '^' y '-' donde no tienen significado especial dentro de "[...]"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // "[]" y "[^]" no son validos: una clase vacia no acepta ningun caracter, asi que se
+        // escribe como la negacion de todos, y negada acepta cualquiera, como '.'
+        if self.ranges.is_empty() && self.classes.is_empty() {
+            return match self.negated {
+                false => write!(f, "[^\0-{}]", char::MAX),
+                true => write!(f, "."),
+            };
+        }
+
+        // "[^]" no es valido, un '^' solo se escribe escapado
+        if !self.negated && self.classes.is_empty() && self.ranges == [('^', '^')] {
+            return write!(f, "\\^");
+        }
+
+        let special = [']', '^', '-'];
+        let mut ranges = vec![];
+        let mut singles = vec![];
+
+        // Los caracteres especiales se sacan de los extremos de los rangos
+        for (from, to) in &self.ranges {
+            let (mut from, mut to) = (*from as u32, *to as u32);
+            while from <= to && special.contains(&char::from_u32(from).unwrap_or('\0')) {
+                singles.push(char::from_u32(from).unwrap_or('\0'));
+                from += 1;
+            }
+            while to >= from && special.contains(&char::from_u32(to).unwrap_or('\0')) {
+                singles.push(char::from_u32(to).unwrap_or('\0'));
+                to -= 1;
+            }
+            if from <= to {
+                if let (Some(from), Some(to)) = (char::from_u32(from), char::from_u32(to)) {
+                    ranges.push((from, to));
+                }
+            }
+        }
+
+        write!(f, "[")?;
+        if self.negated {
+            write!(f, "^")?;
+        }
+        if singles.contains(&']') {
+            write!(f, "]")?;
+        }
+        let mut written = singles.contains(&']');
+        for (from, to) in &ranges {
+            if from == to {
+                write!(f, "{}", from)?;
+            } else {
+                write!(f, "{}-{}", from, to)?;
+            }
+            written = true;
+        }
+        for class_type in &self.classes {
+            write!(f, "[:{}:]", class_type.name())?;
+            written = true;
+        }
+        // Un '^' al principio negaria la clase, en ese caso va despues del '-'
+        let caret = singles.contains(&'^');
+        if caret && (written || self.negated) {
+            write!(f, "^")?;
+        }
+        if singles.contains(&'-') {
+            write!(f, "-")?;
+        }
+        if caret && !(written || self.negated) {
+            write!(f, "^")?;
+        }
+        write!(f, "]")
+    }
+}
@@ -0,0 +1,54 @@
+/// Posicion (en bytes) de un match dentro del texto buscado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Match {
+    pub fn new(start: usize, end: usize) -> Self {
+        Match { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// AS_BYTES: devuelve la porcion del texto que corresponde al match
+    pub fn as_bytes<'h>(&self, haystack: &'h [u8]) -> &'h [u8] {
+        &haystack[self.start..self.end]
+    }
+}
+
+/// Posiciones de cada grupo de captura de un match. El grupo 0 es el match completo
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures {
+    slots: Vec<Option<usize>>,
+}
+
+impl Captures {
+    pub fn new(slots: Vec<Option<usize>>) -> Self {
+        Captures { slots }
+    }
+
+    /// GET: devuelve la posicion del grupo `index`, si participo del match
+    pub fn get(&self, index: usize) -> Option<Match> {
+        match (self.slots.get(index * 2), self.slots.get(index * 2 + 1)) {
+            (Some(Some(start)), Some(Some(end))) => Some(Match::new(*start, *end)),
+            _ => None,
+        }
+    }
+
+    /// LEN: cantidad de grupos, incluyendo el grupo 0
+    pub fn len(&self) -> usize {
+        self.slots.len() / 2
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
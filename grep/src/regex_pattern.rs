@@ -0,0 +1,203 @@
+use crate::regex::{Error, Regex, Span};
+use crate::regex_class::RegexClass;
+use crate::regex_hir::{Hir, HirClass};
+use std::fmt;
+use std::ops::{Bound, RangeBounds};
+
+/// Constructor de expresiones que no pasa por el texto del patron, por lo que nunca hace falta
+/// escapar nada. Produce los mismos pasos que `Regex::new` para la expresion equivalente
+///
+/// `Pattern::literal("a.b").then(Pattern::class(RegexClass::Digit).repeat(1..=3))`
+/// equivale a "a\.b[[:digit:]]{1,3}"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    hir: Hir,
+}
+
+impl Pattern {
+    /// EMPTY: matchea el texto vacio
+    pub fn empty() -> Self {
+        Pattern { hir: Hir::Empty }
+    }
+
+    /// LITERAL: matchea exactamente el texto, sin interpretar metacaracteres
+    pub fn literal(text: &str) -> Self {
+        Pattern {
+            hir: Hir::concat(text.chars().map(Hir::Literal).collect()),
+        }
+    }
+
+    /// ANY: cualquier caracter, como '.'
+    pub fn any() -> Self {
+        Pattern { hir: Hir::Any }
+    }
+
+    /// CLASS: un caracter de la clase, como "[[:digit:]]"
+    pub fn class(class_type: RegexClass) -> Self {
+        Self::set(false, vec![], vec![class_type])
+    }
+
+    /// ONE_OF: uno de los caracteres indicados, como "[abc]"
+    pub fn one_of(chars: &str) -> Self {
+        Self::set(false, chars.chars().map(|c| (c, c)).collect(), vec![])
+    }
+
+    /// NONE_OF: cualquier caracter menos los indicados, como "[^abc]"
+    pub fn none_of(chars: &str) -> Self {
+        Self::set(true, chars.chars().map(|c| (c, c)).collect(), vec![])
+    }
+
+    /// RANGE: un caracter entre `from` y `to` inclusive, como "[a-z]"
+    pub fn range(from: char, to: char) -> Self {
+        Self::set(false, vec![(from.min(to), from.max(to))], vec![])
+    }
+
+    fn set(negated: bool, ranges: Vec<(char, char)>, classes: Vec<RegexClass>) -> Self {
+        let class = HirClass::new(negated, ranges, classes);
+        let hir = match (class.negated, class.ranges.as_slice(), class.classes.len()) {
+            (false, [(from, to)], 0) if from == to => Hir::Literal(*from),
+            _ => Hir::Class(class),
+        };
+        Pattern { hir }
+    }
+
+    /// LINE_START: principio de linea, como '^'
+    pub fn line_start() -> Self {
+        Pattern {
+            hir: Hir::LineStart,
+        }
+    }
+
+    /// LINE_END: fin de linea, como '$'
+    pub fn line_end() -> Self {
+        Pattern { hir: Hir::LineEnd }
+    }
+
+    /// THEN: este patron seguido de `next`
+    pub fn then(self, next: Pattern) -> Self {
+        Pattern {
+            hir: Hir::concat(vec![self.hir, next.hir]),
+        }
+    }
+
+    /// OR: este patron o `other`
+    pub fn or(self, other: Pattern) -> Self {
+        let mut hirs = match self.hir {
+            Hir::Alternation(hirs) => hirs,
+            hir => vec![hir],
+        };
+        hirs.push(other.hir);
+        Pattern {
+            hir: Hir::Alternation(hirs),
+        }
+    }
+
+    /// REPEAT: repite el patron la cantidad de veces del rango, por ejemplo `1..=3` o `2..`
+    pub fn repeat<R: RangeBounds<usize>>(self, range: R) -> Self {
+        let min = match range.start_bound() {
+            Bound::Included(n) => *n,
+            Bound::Excluded(n) => n + 1,
+            Bound::Unbounded => 0,
+        };
+        let max = match range.end_bound() {
+            Bound::Included(m) => Some(*m),
+            Bound::Excluded(m) => Some(m.saturating_sub(1)),
+            Bound::Unbounded => None,
+        };
+        Pattern {
+            hir: Hir::repetition(min, max, self.hir),
+        }
+    }
+
+    /// ZERO_OR_MORE: como '*'
+    pub fn zero_or_more(self) -> Self {
+        self.repeat(..)
+    }
+
+    /// ONE_OR_MORE: como '+'
+    pub fn one_or_more(self) -> Self {
+        self.repeat(1..)
+    }
+
+    /// OPTIONAL: como '?'
+    pub fn optional(self) -> Self {
+        self.repeat(..=1)
+    }
+
+    /// GROUP: captura lo que matchee el patron. Los grupos se numeran de izquierda a derecha
+    /// recien al construir la expresion
+    pub fn group(self) -> Self {
+        Pattern {
+            hir: Hir::Group {
+                index: Some(0),
+                hir: Box::new(self.hir),
+            },
+        }
+    }
+
+    /// TO_HIR: devuelve la representacion simplificada con los grupos ya numerados
+    pub fn to_hir(&self) -> Hir {
+        let mut hir = self.hir.clone();
+        number_groups(&mut hir, &mut 0);
+        hir
+    }
+
+    /// BUILD: arma la expresion
+    pub fn build(&self) -> Result<Regex, Error> {
+        let hir = self.to_hir();
+
+        if let Some((min, max)) = invalid_repetition(&hir) {
+            let pattern = hir.to_string();
+            let needle = format!("{{{},{}}}", min, max);
+            let start = pattern.find(&needle).unwrap_or(0);
+            let end = start + needle.len().min(pattern.len() - start);
+            return Err(Error::BadRepetition {
+                pattern,
+                span: Span::new(start, end),
+            });
+        }
+
+        Regex::from_hir(&hir)
+    }
+}
+
+impl fmt::Display for Pattern {
+    /// Escribe el patron equivalente, con todos los metacaracteres escapados
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hir())
+    }
+}
+
+/// NUMBER_GROUPS: asigna los indices de captura en el orden en que se abren los grupos
+fn number_groups(hir: &mut Hir, next: &mut usize) {
+    match hir {
+        Hir::Group { index, hir } => {
+            if index.is_some() {
+                *next += 1;
+                *index = Some(*next);
+            }
+            number_groups(hir, next);
+        }
+        Hir::Repetition { hir, .. } => number_groups(hir, next),
+        Hir::Concat(hirs) | Hir::Alternation(hirs) => {
+            for hir in hirs {
+                number_groups(hir, next);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// INVALID_REPETITION: busca una repeticion con minimo mayor que maximo
+fn invalid_repetition(hir: &Hir) -> Option<(usize, usize)> {
+    match hir {
+        Hir::Repetition {
+            min,
+            max: Some(max),
+            ..
+        } if min > max => Some((*min, *max)),
+        Hir::Repetition { hir, .. } | Hir::Group { hir, .. } => invalid_repetition(hir),
+        Hir::Concat(hirs) | Hir::Alternation(hirs) => hirs.iter().find_map(invalid_repetition),
+        _ => None,
+    }
+}
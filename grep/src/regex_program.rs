@@ -0,0 +1,442 @@
+use crate::regex_rep::RegexRep;
+use crate::regex_step::RegexStep;
+use crate::regex_val::RegexVal;
+use crate::utils::{decode_char, decode_last_char};
+
+/// Cantidad maxima de instrucciones de un programa, para que repeticiones como "a{1000}{1000}"
+/// no consuman toda la memoria
+pub const SIZE_LIMIT: usize = 100_000;
+
+/// Semantica usada para elegir entre varios matches posibles que empiezan en la misma posicion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchSemantics {
+    /// Gana la primera alternativa que matchea (como perl o el crate regex)
+    #[default]
+    LeftmostFirst,
+    /// Gana el match mas largo (POSIX, como GNU grep)
+    LeftmostLongest,
+}
+
+/// Condicion que tienen que cumplir los bordes de un match ademas del patron. Se verifica al
+/// empezar cada intento y al llegar a `Match`, asi un intento que no la cumple deja lugar a
+/// uno mas corto o que empieza despues
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MatchBoundary {
+    #[default]
+    None,
+    /// Sin caracteres de palabra (letras, digitos o '_') pegados a los bordes (grep -w)
+    Word,
+    /// El match ocupa una linea entera (grep -x)
+    Line,
+}
+
+impl MatchBoundary {
+    /// IS_START: indica si un match puede empezar en `at`
+    pub fn is_start(&self, haystack: &[u8], at: usize) -> bool {
+        match self {
+            MatchBoundary::None => true,
+            MatchBoundary::Word => !decode_last_char(&haystack[..at]).is_some_and(is_word_char),
+            MatchBoundary::Line => at == 0 || haystack[at - 1] == b'\n',
+        }
+    }
+
+    /// IS_END: indica si un match puede terminar en `at`
+    pub fn is_end(&self, haystack: &[u8], at: usize) -> bool {
+        match self {
+            MatchBoundary::None => true,
+            MatchBoundary::Word => !decode_char(&haystack[at..]).is_some_and(is_word_char),
+            MatchBoundary::Line => at == haystack.len() || haystack[at] == b'\n',
+        }
+    }
+}
+
+/// IS_WORD_CHAR: los caracteres que forman palabras para -w
+fn is_word_char((c, _): (char, usize)) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[derive(Debug, Clone)]
+pub enum RegexInst {
+    Consume(RegexVal),   // Consume un caracter aceptado por el valor
+    LineStart,           // ^
+    LineEnd,             // $
+    Split(usize, usize), // Sigue por ambos caminos, el primero tiene prioridad
+    Jump(usize),         // Salta a otra instruccion
+    Save(usize),         // Guarda la posicion actual en un slot de captura
+    Match,               // Se encontro un match
+}
+
+/// Programa compilado a partir de los pasos de una expresion, ejecutado por una maquina de Pike
+#[derive(Debug, Clone)]
+pub struct RegexProgram {
+    insts: Vec<RegexInst>,
+    slots: usize,
+}
+
+impl RegexProgram {
+    /// COMPILE: traduce las alternativas de la expresion a una lista de instrucciones.
+    /// Devuelve None si el programa supera `SIZE_LIMIT`
+    pub fn compile(expression_steps: &[Vec<RegexStep>], groups: usize) -> Option<Self> {
+        let mut program = RegexProgram {
+            insts: vec![],
+            slots: (groups + 1) * 2,
+        };
+
+        program.insts.push(RegexInst::Save(0));
+        program.compile_alternatives(expression_steps);
+        program.insts.push(RegexInst::Save(1));
+        program.insts.push(RegexInst::Match);
+
+        if program.insts.len() > SIZE_LIMIT {
+            return None;
+        }
+
+        Some(program)
+    }
+
+    /// FOLD_CASE: el mismo programa, pero sin distinguir mayusculas de minusculas
+    pub fn fold_case(&self) -> Self {
+        let insts = self
+            .insts
+            .iter()
+            .map(|inst| match inst {
+                RegexInst::Consume(val) => RegexInst::Consume(val.fold_case()),
+                inst => inst.clone(),
+            })
+            .collect();
+        RegexProgram {
+            insts,
+            slots: self.slots,
+        }
+    }
+
+    /// LEN: cantidad de instrucciones del programa
+    pub fn len(&self) -> usize {
+        self.insts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.insts.is_empty()
+    }
+
+    /// SLOTS: cantidad de slots de captura (dos por grupo)
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    fn compile_alternatives(&mut self, alternatives: &[Vec<RegexStep>]) {
+        let mut jumps = vec![];
+
+        for (i, steps) in alternatives.iter().enumerate() {
+            let split = self.insts.len();
+            let is_last = i + 1 == alternatives.len();
+
+            if !is_last {
+                self.insts.push(RegexInst::Split(split + 1, 0));
+            }
+
+            for step in steps {
+                self.compile_step(step);
+            }
+
+            if !is_last {
+                jumps.push(self.insts.len());
+                self.insts.push(RegexInst::Jump(0));
+                let next = self.insts.len();
+                self.insts[split] = RegexInst::Split(split + 1, next);
+            }
+        }
+
+        let end = self.insts.len();
+        for jump in jumps {
+            self.insts[jump] = RegexInst::Jump(end);
+        }
+    }
+
+    fn compile_step(&mut self, step: &RegexStep) {
+        if self.insts.len() > SIZE_LIMIT {
+            return;
+        }
+
+        match step.rep {
+            RegexRep::Exact(n) => self.repeat(n, |program| program.compile_val(&step.val)),
+            RegexRep::Optional => self.compile_optional(&step.val),
+            RegexRep::Any => self.compile_any(&step.val),
+            RegexRep::Range(min, max) => {
+                let min = min.unwrap_or(0);
+                self.repeat(min, |program| program.compile_val(&step.val));
+                match max {
+                    Some(max) => self.repeat(max.saturating_sub(min), |program| {
+                        program.compile_optional(&step.val)
+                    }),
+                    None => self.compile_any(&step.val),
+                }
+            }
+        }
+    }
+
+    /// REPEAT: compila `times` copias, pero corta apenas el programa supera `SIZE_LIMIT`, asi
+    /// una repeticion enorme como "a{100000000000}" falla enseguida. Si una copia no agrega
+    /// instrucciones, las que siguen tampoco
+    fn repeat(&mut self, times: usize, compile: impl Fn(&mut Self)) {
+        for _ in 0..times {
+            let before = self.insts.len();
+            compile(self);
+            if self.insts.len() > SIZE_LIMIT || self.insts.len() == before {
+                break;
+            }
+        }
+    }
+
+    fn compile_optional(&mut self, val: &RegexVal) {
+        let split = self.insts.len();
+        self.insts.push(RegexInst::Split(split + 1, 0));
+        self.compile_val(val);
+        let end = self.insts.len();
+        self.insts[split] = RegexInst::Split(split + 1, end);
+    }
+
+    fn compile_any(&mut self, val: &RegexVal) {
+        let split = self.insts.len();
+        self.insts.push(RegexInst::Split(split + 1, 0));
+        self.compile_val(val);
+        self.insts.push(RegexInst::Jump(split));
+        let end = self.insts.len();
+        self.insts[split] = RegexInst::Split(split + 1, end);
+    }
+
+    fn compile_val(&mut self, val: &RegexVal) {
+        if self.insts.len() > SIZE_LIMIT {
+            return;
+        }
+
+        match val {
+            RegexVal::Group(index, alternatives) => {
+                if let Some(index) = index {
+                    self.insts.push(RegexInst::Save(index * 2));
+                }
+                self.compile_alternatives(alternatives);
+                if let Some(index) = index {
+                    self.insts.push(RegexInst::Save(index * 2 + 1));
+                }
+            }
+            RegexVal::LineStart => self.insts.push(RegexInst::LineStart),
+            RegexVal::LineEnd => self.insts.push(RegexInst::LineEnd),
+            _ => self.insts.push(RegexInst::Consume(val.clone())),
+        }
+    }
+
+    /// SEARCH: busca el primer match a partir de `start` que cumpla con `boundary` y no consuma
+    /// `terminator`, y devuelve los slots de captura. Todos los hilos avanzan en paralelo sobre
+    /// el texto, por lo que no hay backtracking
+    pub fn search(
+        &self,
+        haystack: &[u8],
+        start: usize,
+        semantics: MatchSemantics,
+        boundary: MatchBoundary,
+        terminator: Option<u8>,
+    ) -> Option<Vec<Option<usize>>> {
+        if start > haystack.len() {
+            return None;
+        }
+
+        let mut current = Threads::new(self.insts.len(), self.slots);
+        let mut next = Threads::new(self.insts.len(), self.slots);
+        let mut stack = vec![];
+        let mut slots = vec![None; self.slots];
+        let mut matched: Option<Vec<Option<usize>>> = None;
+        let mut at = start;
+
+        loop {
+            // Mientras no haya match, se prueba empezar uno nuevo en la posicion actual
+            if matched.is_none() && boundary.is_start(haystack, at) {
+                slots.fill(None);
+                self.add_thread(&mut current, &mut stack, 0, at, haystack, &mut slots);
+            }
+
+            if current.is_empty() && matched.is_some() {
+                break;
+            }
+
+            let next_char = decode_char(&haystack[at..]);
+            // Ningun hilo puede consumir el terminador de linea
+            let consumable = next_char.filter(|(c, _)| terminator.map(char::from) != Some(*c));
+
+            for i in 0..current.len() {
+                let pc = current.pc(i);
+                match &self.insts[pc] {
+                    RegexInst::Match => {
+                        // Los hilos que siguen pueden terminar en otro lugar
+                        if !boundary.is_end(haystack, at) {
+                            continue;
+                        }
+                        let candidate = current.slots(pc);
+                        match semantics {
+                            MatchSemantics::LeftmostFirst => {
+                                // Los hilos que siguen tienen menor prioridad
+                                matched = Some(candidate.to_vec());
+                                break;
+                            }
+                            MatchSemantics::LeftmostLongest => {
+                                if is_longer(candidate, matched.as_deref()) {
+                                    matched = Some(candidate.to_vec());
+                                }
+                            }
+                        }
+                    }
+                    RegexInst::Consume(val) => {
+                        if let Some((c, size)) = consumable {
+                            if val.matches_char(c) {
+                                slots.copy_from_slice(current.slots(pc));
+                                self.add_thread(
+                                    &mut next,
+                                    &mut stack,
+                                    pc + 1,
+                                    at + size,
+                                    haystack,
+                                    &mut slots,
+                                );
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            match next_char {
+                Some((_, size)) => at += size,
+                None => break,
+            }
+
+            std::mem::swap(&mut current, &mut next);
+            next.clear();
+        }
+
+        matched
+    }
+
+    /// ADD_THREAD: agrega un hilo siguiendo todas las instrucciones que no consumen caracteres
+    fn add_thread(
+        &self,
+        threads: &mut Threads,
+        stack: &mut Vec<Frame>,
+        pc: usize,
+        at: usize,
+        haystack: &[u8],
+        slots: &mut [Option<usize>],
+    ) {
+        stack.push(Frame::Explore(pc));
+
+        while let Some(frame) = stack.pop() {
+            let pc = match frame {
+                Frame::Explore(pc) => pc,
+                Frame::Restore(slot, value) => {
+                    slots[slot] = value;
+                    continue;
+                }
+            };
+
+            if !threads.insert(pc) {
+                continue;
+            }
+
+            match self.insts[pc] {
+                RegexInst::Jump(target) => stack.push(Frame::Explore(target)),
+                RegexInst::Split(first, second) => {
+                    stack.push(Frame::Explore(second));
+                    stack.push(Frame::Explore(first));
+                }
+                RegexInst::Save(slot) => {
+                    stack.push(Frame::Restore(slot, slots[slot]));
+                    slots[slot] = Some(at);
+                    stack.push(Frame::Explore(pc + 1));
+                }
+                RegexInst::LineStart => {
+                    if at == 0 || haystack[at - 1] == b'\n' {
+                        stack.push(Frame::Explore(pc + 1));
+                    }
+                }
+                RegexInst::LineEnd => {
+                    if at == haystack.len() || haystack[at] == b'\n' {
+                        stack.push(Frame::Explore(pc + 1));
+                    }
+                }
+                RegexInst::Consume(_) | RegexInst::Match => threads.set_slots(pc, slots),
+            }
+        }
+    }
+}
+
+/// IS_LONGER: indica si el candidato empieza antes o, empezando en el mismo lugar, es mas largo
+fn is_longer(candidate: &[Option<usize>], current: Option<&[Option<usize>]>) -> bool {
+    match current {
+        None => true,
+        Some(current) => {
+            candidate[0] < current[0] || (candidate[0] == current[0] && candidate[1] > current[1])
+        }
+    }
+}
+
+enum Frame {
+    Explore(usize),
+    Restore(usize, Option<usize>),
+}
+
+/// Conjunto ordenado de hilos activos, indexado por instruccion
+struct Threads {
+    dense: Vec<usize>,
+    sparse: Vec<usize>,
+    slots: Vec<Option<usize>>,
+    slots_per_thread: usize,
+}
+
+impl Threads {
+    fn new(insts: usize, slots_per_thread: usize) -> Self {
+        Threads {
+            dense: Vec::with_capacity(insts),
+            sparse: vec![0; insts],
+            slots: vec![None; insts * slots_per_thread],
+            slots_per_thread,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.dense.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.dense.is_empty()
+    }
+
+    fn pc(&self, i: usize) -> usize {
+        self.dense[i]
+    }
+
+    fn contains(&self, pc: usize) -> bool {
+        let i = self.sparse[pc];
+        i < self.dense.len() && self.dense[i] == pc
+    }
+
+    fn insert(&mut self, pc: usize) -> bool {
+        if self.contains(pc) {
+            return false;
+        }
+        self.sparse[pc] = self.dense.len();
+        self.dense.push(pc);
+        true
+    }
+
+    fn slots(&self, pc: usize) -> &[Option<usize>] {
+        &self.slots[pc * self.slots_per_thread..(pc + 1) * self.slots_per_thread]
+    }
+
+    fn set_slots(&mut self, pc: usize, slots: &[Option<usize>]) {
+        self.slots[pc * self.slots_per_thread..(pc + 1) * self.slots_per_thread]
+            .copy_from_slice(slots);
+    }
+
+    fn clear(&mut self) {
+        self.dense.clear();
+    }
+}
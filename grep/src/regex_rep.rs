@@ -1,9 +1,8 @@
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RegexRep {
     // Repetition
     Any,                                 // *
     Exact(usize),                        // {n}
-    Range(Option<usize>, Option<usize>), // {n,m}
+    Range(Option<usize>, Option<usize>), // {n,m} y +
     Optional,                            // ?
-    Last,                                // $
 }
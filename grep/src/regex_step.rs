@@ -1,6 +1,6 @@
 use crate::{regex_rep::RegexRep, regex_val::RegexVal};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RegexStep {
     pub val: RegexVal,
     pub rep: RegexRep,
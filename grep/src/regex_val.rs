@@ -1,135 +1,140 @@
 use crate::regex_class::RegexClass;
+use crate::regex_hir::HirClass;
+use crate::regex_step::RegexStep;
+use crate::utils::decode_char;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum RegexVal {
     Literal(char), // Caracteres normales
     Wildcard,
     Allowed(Vec<char>),
     NotAllowed(Vec<char>),
     Class(RegexClass),
+    // [...] con rangos y clases mezclados
+    Set {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+        classes: Vec<RegexClass>,
+    },
+    Group(Option<usize>, Vec<Vec<RegexStep>>), // (...) con el indice de su captura
+    LineStart,                                 // ^
+    LineEnd,                                   // $
 }
 
 impl RegexVal {
-    pub fn matches(&self, value: &str) -> usize {
-        //println!("matching value '{}' with {:?}", value, self);
+    /// MATCHES: devuelve cuantos bytes consume el valor al principio de `value` (0 si no matchea)
+    pub fn matches(&self, value: impl AsRef<[u8]>) -> usize {
+        match decode_char(value.as_ref()) {
+            Some((c, size)) if self.matches_char(c) => size,
+            _ => 0,
+        }
+    }
+
+    /// MATCHES_CHAR: indica si el caracter es aceptado por el valor
+    pub fn matches_char(&self, c: char) -> bool {
         match self {
-            Self::Literal(l) => {
-                if Some(*l) == value.chars().next() {
-                    l.len_utf8()
-                } else {
-                    0
-                }
-            }
-            Self::Wildcard => {
-                if let Some(w) = value.chars().next() {
-                    w.len_utf8()
-                } else {
-                    0
-                }
+            Self::Literal(l) => *l == c,
+            Self::Wildcard => true,
+            Self::Allowed(v) => v.contains(&c),
+            Self::NotAllowed(v) => !v.contains(&c),
+            Self::Class(class_type) => class_type.matches(c),
+            Self::Set {
+                negated,
+                ranges,
+                classes,
+            } => {
+                let found = ranges.iter().any(|(from, to)| *from <= c && c <= *to)
+                    || classes.iter().any(|class_type| class_type.matches(c));
+                found != *negated
             }
-            Self::Allowed(v) => {
-                if let Some(a) = value.chars().next() {
-                    if v.contains(&a) {
-                        a.len_utf8()
-                    } else {
-                        0
+            // Los grupos y las anclas no consumen caracteres por si solos
+            Self::Group(_, _) | Self::LineStart | Self::LineEnd => false,
+        }
+    }
+
+    /// FOLD_CASE: el valor que acepta ademas las mayusculas y minusculas de lo que acepta. Las
+    /// negaciones excluyen todas las variantes: "[^a]" tampoco acepta 'A'
+    pub fn fold_case(&self) -> RegexVal {
+        let fold_chars = |chars: &[char]| {
+            let mut folded: Vec<char> = chars.iter().flat_map(|c| case_variants(*c)).collect();
+            folded.sort();
+            folded.dedup();
+            folded
+        };
+
+        match self {
+            Self::Literal(c) => match case_variants(*c).as_slice() {
+                [_] => Self::Literal(*c),
+                variants => Self::Allowed(variants.to_vec()),
+            },
+            Self::Allowed(v) => Self::Allowed(fold_chars(v)),
+            Self::NotAllowed(v) => Self::NotAllowed(fold_chars(v)),
+            Self::Class(class_type) => Self::Set {
+                negated: false,
+                ranges: vec![],
+                classes: fold_classes(&[*class_type]),
+            },
+            Self::Set {
+                negated,
+                ranges,
+                classes,
+            } => {
+                let mut folded = ranges.clone();
+                for (from, to) in ranges {
+                    for c in *from..=*to {
+                        folded.extend(case_variants(c).into_iter().map(|v| (v, v)));
                     }
-                } else {
-                    0
                 }
-            }
-            Self::NotAllowed(v) => {
-                if let Some(a) = value.chars().next() {
-                    if !v.contains(&a) {
-                        a.len_utf8()
-                    } else {
-                        0
-                    }
-                } else {
-                    0
+                let class = HirClass::new(*negated, folded, fold_classes(classes));
+                Self::Set {
+                    negated: class.negated,
+                    ranges: class.ranges,
+                    classes: class.classes,
                 }
             }
-            Self::Class(class_type) => match class_type {
-                RegexClass::Alphanumeric => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_alphanumeric() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Alphabetic => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_alphabetic() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Digit => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_digit(10) {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Lowercase => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_lowercase() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Uppercase => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_uppercase() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Whitespace => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_whitespace() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-                RegexClass::Punctuation => {
-                    if let Some(a) = value.chars().next() {
-                        if a.is_ascii_punctuation() {
-                            a.len_utf8()
-                        } else {
-                            0
-                        }
-                    } else {
-                        0
-                    }
-                }
-            },
+            _ => self.clone(),
+        }
+    }
+}
+
+/// CASE_VARIANTS: el caracter y sus versiones en mayuscula y minuscula, si son de un solo
+/// caracter
+fn case_variants(c: char) -> Vec<char> {
+    if !(c.is_alphabetic() || c.is_lowercase() || c.is_uppercase()) {
+        return vec![c];
+    }
+
+    let single = |mapped: String| {
+        let mut chars = mapped.chars();
+        match (chars.next(), chars.next()) {
+            (Some(variant), None) => Some(variant),
+            _ => None,
         }
+    };
+
+    let mut variants = vec![c];
+    let mapped = [c.to_lowercase().to_string(), c.to_uppercase().to_string()];
+    for variant in mapped.into_iter().filter_map(single) {
+        if !variants.contains(&variant) {
+            variants.push(variant);
+        }
+    }
+    variants
+}
+
+/// FOLD_CLASSES: con mayusculas y minusculas indistintas, "[:upper:]" y "[:lower:]" aceptan
+/// las dos
+fn fold_classes(classes: &[RegexClass]) -> Vec<RegexClass> {
+    let mut folded = classes.to_vec();
+    if classes
+        .iter()
+        .any(|c| matches!(c, RegexClass::Lowercase | RegexClass::Uppercase))
+    {
+        folded.extend([RegexClass::Lowercase, RegexClass::Uppercase]);
     }
+    folded.sort();
+    folded.dedup();
+    folded
 }
 
 #[cfg(test)]
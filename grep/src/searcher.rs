@@ -0,0 +1,607 @@
+use crate::line_reader::{LineReader, DEFAULT_CAPACITY};
+use crate::matcher::Matcher;
+use crate::mmap::Mmap;
+use crate::utils::{find_byte, rfind_byte};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read};
+
+/// Cantidad de bytes del principio de la fuente que se revisan para saber si es binaria
+const BINARY_PREFIX: usize = DEFAULT_CAPACITY;
+
+/// Lineas que contienen un match (o que no lo contienen, si la busqueda es invertida), tal
+/// como aparecen en la fuente (con sus '\n')
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SinkMatch<'b> {
+    pub bytes: &'b [u8],
+    pub absolute_offset: u64,     // Posicion en la fuente del primer byte
+    pub line_number: Option<u64>, // Numero de la primera linea, si se cuentan las lineas
+}
+
+/// Linea que se entrega como contexto de un resultado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SinkContext<'b> {
+    pub bytes: &'b [u8],
+    pub absolute_offset: u64,
+    pub line_number: Option<u64>,
+    pub kind: ContextKind,
+}
+
+/// Si una linea de contexto esta antes o despues del resultado
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextKind {
+    Before,
+    After,
+}
+
+/// Destino de los resultados de una busqueda
+pub trait Sink {
+    /// MATCHED: recibe las lineas de un match. Devuelve false para terminar la busqueda
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool>;
+
+    /// CONTEXT: recibe una linea de contexto. Devuelve false para terminar la busqueda
+    fn context(&mut self, _context: &SinkContext) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    /// CONTEXT_BREAK: avisa que lo proximo que se entrega no es contiguo a lo anterior
+    fn context_break(&mut self) -> io::Result<bool> {
+        Ok(true)
+    }
+
+    /// BINARY_DATA: avisa, antes de entregar cualquier resultado, que la fuente es binaria y
+    /// en que posicion se noto. Devuelve false para no buscar en ella
+    fn binary_data(&mut self, _offset: u64) -> io::Result<bool> {
+        Ok(true)
+    }
+}
+
+/// Busca sobre bloques de muchas lineas en lugar de linea por linea: el buscador recorre todo el
+/// bloque y solo cuando encuentra un match se ubican los limites de la linea que lo contiene.
+///
+/// Salvo en modo multilinea, un match nunca abarca un '\n'; si el buscador devuelve uno asi, la
+/// linea donde empieza se vuelve a buscar sola
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Searcher {
+    multi_line: bool,
+    memory_map: bool,
+    invert_match: bool,
+    line_number: bool,
+    before_context: usize,
+    after_context: usize,
+    binary_detection: bool,
+}
+
+impl Searcher {
+    pub fn new() -> Self {
+        Searcher::default()
+    }
+
+    /// MULTI_LINE: permite que un match abarque varias lineas. En ese modo la fuente se lee
+    /// entera antes de buscar
+    pub fn multi_line(mut self, yes: bool) -> Self {
+        self.multi_line = yes;
+        self
+    }
+
+    /// INVERT_MATCH: entrega las lineas que no matchean, de a una
+    pub fn invert_match(mut self, yes: bool) -> Self {
+        self.invert_match = yes;
+        self
+    }
+
+    /// LINE_NUMBER: cuenta las lineas para informar el numero de cada resultado. Solo se
+    /// cuentan los '\n' de lo que se va salteando, no se recorre el texto linea por linea
+    pub fn line_number(mut self, yes: bool) -> Self {
+        self.line_number = yes;
+        self
+    }
+
+    /// BEFORE_CONTEXT: entrega tambien las `lines` lineas anteriores a cada resultado
+    pub fn before_context(mut self, lines: usize) -> Self {
+        self.before_context = lines;
+        self
+    }
+
+    /// AFTER_CONTEXT: entrega tambien las `lines` lineas posteriores a cada resultado. Las
+    /// ventanas de resultados cercanos se unen, ninguna linea se entrega dos veces
+    pub fn after_context(mut self, lines: usize) -> Self {
+        self.after_context = lines;
+        self
+    }
+
+    /// BINARY_DETECTION: revisa el principio de la fuente y, si tiene un byte NUL o no es
+    /// UTF-8 valido, avisa con `Sink::binary_data`
+    pub fn binary_detection(mut self, yes: bool) -> Self {
+        self.binary_detection = yes;
+        self
+    }
+
+    /// MEMORY_MAP: en `search_file`, mapea el archivo en memoria y lo busca como un solo texto
+    pub fn memory_map(mut self, yes: bool) -> Self {
+        self.memory_map = yes;
+        self
+    }
+
+    /// SEARCH_FILE: busca en un archivo abierto. Si no se puede mapear (un pipe, un archivo
+    /// especial o un sistema que no lo permite) se lee como cualquier otra fuente
+    pub fn search_file<M, S>(&self, matcher: &M, file: &File, sink: &mut S) -> io::Result<()>
+    where
+        M: Matcher + ?Sized,
+        S: Sink,
+    {
+        if self.memory_map {
+            if let Ok(map) = Mmap::map(file) {
+                return self.search_slice(matcher, &map, sink);
+            }
+        }
+        self.search_reader(matcher, file, sink)
+    }
+
+    /// SEARCH_READER: busca en una fuente leyendola de a bloques de lineas completas
+    pub fn search_reader<M, R, S>(&self, matcher: &M, source: R, sink: &mut S) -> io::Result<()>
+    where
+        M: Matcher + ?Sized,
+        R: Read,
+        S: Sink,
+    {
+        if self.multi_line {
+            let mut bytes = vec![];
+            let mut source = source;
+            source.read_to_end(&mut bytes)?;
+            return self.search_slice(matcher, &bytes, sink);
+        }
+
+        let mut reader = LineReader::new(source);
+        let mut state = SearchState::new(self.before_context);
+        loop {
+            let offset = reader.offset();
+            let block = reader.fill()?;
+            if block.is_empty() {
+                return Ok(());
+            }
+            let len = block.len();
+            if offset == 0 && !self.check_binary(block, sink)? {
+                return Ok(());
+            }
+            if !self.search_block(matcher, block, offset, &mut state, sink)? {
+                return Ok(());
+            }
+            reader.consume(len);
+        }
+    }
+
+    /// SEARCH_SLICE: busca en un texto que ya esta entero en memoria
+    pub fn search_slice<M, S>(&self, matcher: &M, haystack: &[u8], sink: &mut S) -> io::Result<()>
+    where
+        M: Matcher + ?Sized,
+        S: Sink,
+    {
+        if !self.check_binary(haystack, sink)? {
+            return Ok(());
+        }
+        let mut state = SearchState::new(self.before_context);
+        self.search_block(matcher, haystack, 0, &mut state, sink)
+            .map(|_| ())
+    }
+
+    /// CHECK_BINARY: si la deteccion esta activa, revisa el principio de la fuente y avisa al
+    /// destino si es binaria. Devuelve false si el destino no quiere buscar en ella
+    fn check_binary<S: Sink>(&self, bytes: &[u8], sink: &mut S) -> io::Result<bool> {
+        if !self.binary_detection {
+            return Ok(true);
+        }
+        match binary_offset(&bytes[..bytes.len().min(BINARY_PREFIX)]) {
+            Some(offset) => sink.binary_data(offset as u64),
+            None => Ok(true),
+        }
+    }
+
+    /// SEARCH_BLOCK: busca en un bloque de lineas completas que empieza en `offset` de la
+    /// fuente, continuando el estado de los bloques anteriores. Devuelve false si el destino
+    /// pidio terminar
+    fn search_block<M, S>(
+        &self,
+        matcher: &M,
+        block: &[u8],
+        offset: u64,
+        state: &mut SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool>
+    where
+        M: Matcher + ?Sized,
+        S: Sink,
+    {
+        let mut block = Block {
+            bytes: block,
+            offset,
+            counter: LineCounter {
+                enabled: self.line_number,
+                lines: state.lines,
+                counted: 0,
+            },
+        };
+        if state.stopped {
+            return self.trailing_context(&mut block, 0, state, sink);
+        }
+
+        let mut next_match = None;
+        let mut pos = 0;
+        loop {
+            let selected = match self.invert_match {
+                true => self.find_unmatched(matcher, block.bytes, pos, &mut next_match),
+                false => self.find_lines(matcher, block.bytes, pos),
+            };
+
+            // Las lineas que quedan entre dos resultados solo pueden ser contexto
+            let until = selected.map_or(block.bytes.len(), |(start, _)| start);
+            if !self.context_lines(&mut block, pos, until, selected.is_some(), state, sink)? {
+                return Ok(false);
+            }
+
+            let Some((line_start, line_end)) = selected else {
+                break;
+            };
+            if !self.context_break(&block, line_start, state, sink)? {
+                return Ok(false);
+            }
+            let lines = SinkMatch {
+                bytes: &block.bytes[line_start..line_end],
+                absolute_offset: offset + line_start as u64,
+                line_number: block.counter.line_at(block.bytes, line_start),
+            };
+            let more = sink.matched(&lines)?;
+            state.printed_until = Some(offset + line_end as u64);
+            state.after = self.after_context;
+            state.before.clear();
+            if !more {
+                state.stopped = true;
+                return self.trailing_context(&mut block, line_end, state, sink);
+            }
+            pos = line_end;
+        }
+
+        block.counter.line_at(block.bytes, block.bytes.len());
+        state.lines = block.counter.lines;
+        Ok(true)
+    }
+
+    /// CONTEXT_LINES: entrega como contexto las lineas de `start..end` que estan despues de un
+    /// resultado o, si `selected` indica que a continuacion viene otro, antes de el. Las que
+    /// llegan al final del bloque se guardan, porque pueden ser el contexto anterior de un
+    /// resultado del proximo bloque
+    fn context_lines<S: Sink>(
+        &self,
+        block: &mut Block,
+        start: usize,
+        end: usize,
+        selected: bool,
+        state: &mut SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool> {
+        if self.before_context == 0 && self.after_context == 0 {
+            return Ok(true);
+        }
+
+        let mut pos = start;
+        while state.after > 0 && pos < end {
+            let line_end = line_end(block.bytes, pos);
+            if !self.context_line(block, pos, line_end, ContextKind::After, state, sink)? {
+                return Ok(false);
+            }
+            state.after -= 1;
+            pos = line_end;
+        }
+
+        // Las ultimas lineas antes del proximo resultado, sin volver sobre las ya entregadas
+        let mut before_start = end;
+        let mut count = 0;
+        while count < self.before_context && before_start > pos {
+            before_start =
+                rfind_byte(b'\n', &block.bytes[pos..before_start - 1]).map_or(pos, |i| pos + i + 1);
+            count += 1;
+        }
+
+        if !selected {
+            for line in block.bytes[before_start..end].split_inclusive(|b| *b == b'\n') {
+                let line_number = block.counter.line_at(block.bytes, before_start);
+                let absolute_offset = block.offset + before_start as u64;
+                state.before.push(line, absolute_offset, line_number);
+                before_start += line.len();
+            }
+            return Ok(true);
+        }
+
+        // Si no alcanzan las lineas del bloque, siguen las guardadas del bloque anterior
+        if before_start == 0 {
+            let missing = self.before_context - count;
+            let saved = std::mem::take(&mut state.before);
+            let skip = saved.len().saturating_sub(missing);
+            for line in saved.lines.iter().skip(skip) {
+                if state
+                    .printed_until
+                    .is_some_and(|until| line.absolute_offset < until)
+                {
+                    continue;
+                }
+                let context = SinkContext {
+                    bytes: &line.bytes,
+                    absolute_offset: line.absolute_offset,
+                    line_number: line.line_number,
+                    kind: ContextKind::Before,
+                };
+                if !self.emit_context(&context, state, sink)? {
+                    return Ok(false);
+                }
+            }
+            state.before = saved;
+        }
+
+        while before_start < end {
+            let line_end = line_end(block.bytes, before_start);
+            let kind = ContextKind::Before;
+            if !self.context_line(block, before_start, line_end, kind, state, sink)? {
+                return Ok(false);
+            }
+            before_start = line_end;
+        }
+        Ok(true)
+    }
+
+    /// TRAILING_CONTEXT: cuando el destino pidio terminar (por ejemplo, con -m), igual entrega
+    /// como contexto posterior las lineas que siguen al ultimo resultado, matcheen o no.
+    /// Devuelve true si todavia faltan lineas del proximo bloque
+    fn trailing_context<S: Sink>(
+        &self,
+        block: &mut Block,
+        mut pos: usize,
+        state: &mut SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool> {
+        while state.after > 0 && pos < block.bytes.len() {
+            let line_end = line_end(block.bytes, pos);
+            if !self.context_line(block, pos, line_end, ContextKind::After, state, sink)? {
+                return Ok(false);
+            }
+            state.after -= 1;
+            pos = line_end;
+        }
+        block.counter.line_at(block.bytes, block.bytes.len());
+        state.lines = block.counter.lines;
+        Ok(state.after > 0)
+    }
+
+    /// CONTEXT_LINE: entrega una linea del bloque como contexto
+    fn context_line<S: Sink>(
+        &self,
+        block: &mut Block,
+        start: usize,
+        end: usize,
+        kind: ContextKind,
+        state: &mut SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool> {
+        let context = SinkContext {
+            bytes: &block.bytes[start..end],
+            absolute_offset: block.offset + start as u64,
+            line_number: block.counter.line_at(block.bytes, start),
+            kind,
+        };
+        self.emit_context(&context, state, sink)
+    }
+
+    /// EMIT_CONTEXT: entrega una linea de contexto, separandola del grupo anterior si no es
+    /// contigua
+    fn emit_context<S: Sink>(
+        &self,
+        context: &SinkContext,
+        state: &mut SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool> {
+        if state
+            .printed_until
+            .is_some_and(|until| until != context.absolute_offset)
+            && !sink.context_break()?
+        {
+            return Ok(false);
+        }
+        state.printed_until = Some(context.absolute_offset + context.bytes.len() as u64);
+        sink.context(context)
+    }
+
+    /// CONTEXT_BREAK: avisa al destino que el resultado que empieza en `start` no es contiguo a
+    /// lo ultimo que se entrego
+    fn context_break<S: Sink>(
+        &self,
+        block: &Block,
+        start: usize,
+        state: &SearchState,
+        sink: &mut S,
+    ) -> io::Result<bool> {
+        match state.printed_until {
+            Some(until) if until != block.offset + start as u64 => sink.context_break(),
+            _ => Ok(true),
+        }
+    }
+
+    /// FIND_UNMATCHED: ubica la proxima linea que no matchea desde `pos`, que es un principio
+    /// de linea. `next_match` guarda el proximo match del bloque, asi no se vuelve a buscar
+    /// para cada linea
+    fn find_unmatched<M>(
+        &self,
+        matcher: &M,
+        block: &[u8],
+        mut pos: usize,
+        next_match: &mut Option<Option<(usize, usize)>>,
+    ) -> Option<(usize, usize)>
+    where
+        M: Matcher + ?Sized,
+    {
+        while pos < block.len() {
+            let found = match *next_match {
+                Some(found) if found.is_none_or(|(start, _)| start >= pos) => found,
+                _ => {
+                    let found = self.find_lines(matcher, block, pos);
+                    *next_match = Some(found);
+                    found
+                }
+            };
+            match found {
+                Some((start, end)) if start == pos => pos = end,
+                _ => return Some((pos, line_end(block, pos))),
+            }
+        }
+        None
+    }
+
+    /// FIND_LINES: ubica las lineas del proximo match que empiece en `pos` o despues
+    fn find_lines<M>(&self, matcher: &M, block: &[u8], mut pos: usize) -> Option<(usize, usize)>
+    where
+        M: Matcher + ?Sized,
+    {
+        while pos < block.len() {
+            let found = matcher.find_at(block, pos)?;
+            // Un match vacio despues del ultimo '\n' no pertenece a ninguna linea
+            if found.start == block.len() && block.ends_with(b"\n") {
+                return None;
+            }
+
+            let line_start =
+                rfind_byte(b'\n', &block[pos..found.start]).map_or(pos, |i| pos + i + 1);
+            let spans_lines = find_byte(b'\n', &block[found.start..found.end]).is_some();
+
+            if spans_lines && !self.multi_line {
+                let line_end = line_end(block, found.start);
+                let line = &block[line_start..line_end];
+                let text = line.strip_suffix(b"\n").unwrap_or(line);
+                if matcher.is_match(text) {
+                    return Some((line_start, line_end));
+                }
+                pos = line_end;
+                continue;
+            }
+
+            // La linea del ultimo byte del match; un match vacio esta en la linea donde empieza
+            let line_end = line_end(block, found.start.max(found.end.saturating_sub(1)));
+            return Some((line_start, line_end));
+        }
+
+        None
+    }
+}
+
+/// Lo que la busqueda arrastra de un bloque al siguiente
+#[derive(Default)]
+struct SearchState {
+    lines: u64,                 // Lineas de los bloques anteriores
+    after: usize,               // Lineas de contexto posterior que faltan entregar
+    printed_until: Option<u64>, // Fin de lo ultimo entregado, resultado o contexto
+    before: ContextBuffer,      // Ultimas lineas sin entregar del bloque anterior
+    stopped: bool,              // El destino pidio terminar, solo falta el contexto posterior
+}
+
+impl SearchState {
+    fn new(before_context: usize) -> Self {
+        SearchState {
+            before: ContextBuffer {
+                lines: VecDeque::with_capacity(before_context),
+                capacity: before_context,
+            },
+            ..SearchState::default()
+        }
+    }
+}
+
+/// Un bloque con su posicion en la fuente y la cuenta de lineas hasta donde se avanzo
+struct Block<'b> {
+    bytes: &'b [u8],
+    offset: u64,
+    counter: LineCounter,
+}
+
+/// Buffer circular con las ultimas lineas leidas, para el contexto anterior de un resultado
+/// que aparece al principio de un bloque. Las lineas se copian porque el bloque donde estaban
+/// se reutiliza; al descartar la mas vieja se reutiliza su memoria
+#[derive(Default)]
+struct ContextBuffer {
+    lines: VecDeque<ContextLine>,
+    capacity: usize,
+}
+
+struct ContextLine {
+    bytes: Vec<u8>,
+    absolute_offset: u64,
+    line_number: Option<u64>,
+}
+
+impl ContextBuffer {
+    fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// PUSH: agrega una linea, descartando la mas vieja si ya hay `capacity`
+    fn push(&mut self, bytes: &[u8], absolute_offset: u64, line_number: Option<u64>) {
+        let mut line = match self.lines.len() >= self.capacity {
+            true => match self.lines.pop_front() {
+                Some(line) => line,
+                None => return,
+            },
+            false => ContextLine {
+                bytes: vec![],
+                absolute_offset,
+                line_number,
+            },
+        };
+        line.bytes.clear();
+        line.bytes.extend_from_slice(bytes);
+        line.absolute_offset = absolute_offset;
+        line.line_number = line_number;
+        self.lines.push_back(line);
+    }
+
+    fn clear(&mut self) {
+        self.lines.clear();
+    }
+}
+
+/// Cuenta los '\n' de un bloque a medida que avanza la busqueda
+struct LineCounter {
+    enabled: bool,
+    lines: u64,     // Lineas terminadas antes de `counted`
+    counted: usize, // Hasta donde se contaron los '\n' del bloque
+}
+
+impl LineCounter {
+    /// LINE_AT: numero de la linea que empieza en `pos`, que no puede estar antes de la ultima
+    /// posicion consultada
+    fn line_at(&mut self, block: &[u8], pos: usize) -> Option<u64> {
+        if !self.enabled {
+            return None;
+        }
+        self.lines += block[self.counted..pos]
+            .iter()
+            .filter(|b| **b == b'\n')
+            .count() as u64;
+        self.counted = pos;
+        Some(self.lines + 1)
+    }
+}
+
+/// LINE_END: fin (despues del '\n') de la linea que contiene la posicion `at`
+fn line_end(block: &[u8], at: usize) -> usize {
+    find_byte(b'\n', &block[at..]).map_or(block.len(), |i| at + i + 1)
+}
+
+/// BINARY_OFFSET: posicion del primer byte NUL o de la primera secuencia que no es UTF-8
+/// valido. Un caracter cortado al final no cuenta, puede seguir despues del prefijo revisado
+fn binary_offset(bytes: &[u8]) -> Option<usize> {
+    let invalid = match std::str::from_utf8(bytes) {
+        Err(err) if err.error_len().is_some() => Some(err.valid_up_to()),
+        _ => None,
+    };
+    let nul = find_byte(0, bytes);
+    match (nul, invalid) {
+        (Some(nul), Some(invalid)) => Some(nul.min(invalid)),
+        (nul, invalid) => nul.or(invalid),
+    }
+}
@@ -0,0 +1,162 @@
+use crate::matcher::Matcher;
+use crate::regex_match::Captures;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Plantilla de --format para escribir cada match. Los datos del match se escriben con:
+///
+/// - `{path}`: el nombre del archivo
+/// - `{line}`: el numero de la linea donde empieza el match
+/// - `{col}`: la columna donde empieza el match (desde 1)
+/// - `{byte}`: la posicion en bytes del match dentro del archivo
+/// - `{text}`: las lineas que contienen el match, sin el ultimo '\n'
+/// - `{match}`: el match completo
+/// - `{1}`, `{nombre}`: un grupo de captura por numero o por nombre; vacio si no participo
+///
+/// Las secuencias `\t`, `\n`, `\r`, `\0`, `\\`, `\{` y `\}` escriben ese caracter. Cada registro
+/// termina con un '\n'
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Path,
+    Line,
+    Column,
+    Byte,
+    Text,
+    Group(usize), // El 0 es el match completo
+}
+
+/// Datos de un match para completar la plantilla
+#[derive(Debug, Clone, Copy)]
+pub struct Record<'r> {
+    pub path: &'r str,
+    pub line_number: Option<u64>,
+    pub column: usize,
+    pub byte_offset: u64,
+    pub text: &'r [u8],     // Las lineas del match, sin el ultimo '\n'
+    pub haystack: &'r [u8], // Donde estan las posiciones de `captures`
+    pub captures: Option<&'r Captures>,
+}
+
+/// Errores al leer una plantilla
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    UnclosedBrace,
+    UnmatchedBrace,
+    UnknownPlaceholder(String),
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TemplateError::UnclosedBrace => write!(f, "invalid format: unclosed '{{'"),
+            TemplateError::UnmatchedBrace => write!(f, "invalid format: unmatched '}}'"),
+            TemplateError::UnknownPlaceholder(name) => {
+                write!(f, "invalid format: unknown placeholder '{{{}}}'", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+impl Template {
+    /// PARSE: lee la plantilla. Los grupos de captura tienen que existir en `matcher`
+    pub fn parse(template: &str, matcher: &dyn Matcher) -> Result<Template, TemplateError> {
+        let mut pieces = vec![];
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => literal.push('\t'),
+                    Some('n') => literal.push('\n'),
+                    Some('r') => literal.push('\r'),
+                    Some('0') => literal.push('\0'),
+                    Some(c @ ('\\' | '{' | '}')) => literal.push(c),
+                    // Una secuencia desconocida se escribe tal cual
+                    Some(c) => {
+                        literal.push('\\');
+                        literal.push(c);
+                    }
+                    None => literal.push('\\'),
+                },
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::UnclosedBrace),
+                        }
+                    }
+                    if !literal.is_empty() {
+                        pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+                    }
+                    pieces.push(Self::placeholder(name, matcher)?);
+                }
+                '}' => return Err(TemplateError::UnmatchedBrace),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+        Ok(Template { pieces })
+    }
+
+    /// PLACEHOLDER: el dato que corresponde a un `{nombre}`
+    fn placeholder(name: String, matcher: &dyn Matcher) -> Result<Piece, TemplateError> {
+        let piece = match name.as_str() {
+            "path" => Piece::Path,
+            "line" => Piece::Line,
+            "col" => Piece::Column,
+            "byte" => Piece::Byte,
+            "text" => Piece::Text,
+            "match" => Piece::Group(0),
+            _ => {
+                let index = match name.parse::<usize>() {
+                    Ok(index) => Some(index).filter(|index| *index < matcher.captures_len()),
+                    Err(_) => matcher.capture_index(&name),
+                };
+                match index {
+                    Some(index) => Piece::Group(index),
+                    None => return Err(TemplateError::UnknownPlaceholder(name)),
+                }
+            }
+        };
+        Ok(piece)
+    }
+
+    /// RENDER: escribe el registro de un match, terminado en '\n'
+    pub fn render<W: Write>(&self, out: &mut W, record: &Record) -> io::Result<()> {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(text) => out.write_all(text.as_bytes())?,
+                Piece::Path => out.write_all(record.path.as_bytes())?,
+                Piece::Line => {
+                    if let Some(line_number) = record.line_number {
+                        write!(out, "{}", line_number)?;
+                    }
+                }
+                Piece::Column => write!(out, "{}", record.column)?,
+                Piece::Byte => write!(out, "{}", record.byte_offset)?,
+                Piece::Text => out.write_all(record.text)?,
+                Piece::Group(index) => {
+                    let group = record.captures.and_then(|captures| captures.get(*index));
+                    if let Some(group) = group {
+                        out.write_all(group.as_bytes(record.haystack))?;
+                    }
+                }
+            }
+        }
+        out.write_all(b"\n")
+    }
+}
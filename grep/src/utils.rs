@@ -1,27 +1,17 @@
-use std::env;
 use std::fs::File;
 use std::io::BufRead;
-use std::io::{BufReader, Error, ErrorKind};
-
-/// READ_ARGS: lee los argumentos pasados por comando y devuelve una lista con ellos
-pub fn read_args() -> Result<Vec<String>, std::io::Error> {
-    let args: Vec<String> = env::args().collect();
-
-    if args.len() < 3 {
-        Error::new(ErrorKind::Other, "Missing arguments");
-    } else if args.len() > 3 {
-        Error::new(ErrorKind::Other, "Too many arguments");
-    }
-
-    Ok(args)
-}
+use std::io::{BufReader, Read};
 
 /// READ_LINES: lee el archivo indicado y devuelve una lista con cada linea
 pub fn read_lines(filename: String) -> Result<Vec<String>, std::io::Error> {
-    let mut rows = Vec::new();
     let file = File::open(filename)?;
+    read_lines_from(file)
+}
 
-    let reader = BufReader::new(file);
+/// READ_LINES_FROM: como `read_lines`, pero lee de cualquier fuente (por ejemplo stdin)
+pub fn read_lines_from<R: Read>(source: R) -> Result<Vec<String>, std::io::Error> {
+    let mut rows = Vec::new();
+    let reader = BufReader::new(source);
 
     for line in reader.lines() {
         rows.push(line?);
@@ -29,3 +19,49 @@ pub fn read_lines(filename: String) -> Result<Vec<String>, std::io::Error> {
 
     Ok(rows)
 }
+
+/// DECODE_CHAR: decodifica el primer caracter UTF-8 de `bytes` y devuelve su tamaño en bytes.
+/// Un byte invalido se interpreta como U+FFFD de tamaño 1
+pub fn decode_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let first = *bytes.first()?;
+    let width = match first {
+        0x00..=0x7F => return Some((first as char, 1)),
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return Some((char::REPLACEMENT_CHARACTER, 1)),
+    };
+
+    match bytes.get(..width).map(std::str::from_utf8) {
+        Some(Ok(s)) => s.chars().next().map(|c| (c, width)),
+        _ => Some((char::REPLACEMENT_CHARACTER, 1)),
+    }
+}
+
+/// DECODE_LAST_CHAR: decodifica el ultimo caracter UTF-8 de `bytes`, devolviendo tambien su
+/// tamaño. Un byte invalido al final se toma como U+FFFD de un byte
+pub fn decode_last_char(bytes: &[u8]) -> Option<(char, usize)> {
+    let last = *bytes.last()?;
+    if last.is_ascii() {
+        return Some((last as char, 1));
+    }
+
+    // El primer byte del caracter es el ultimo que no es de continuacion (10xxxxxx)
+    let start = (bytes.len().saturating_sub(4)..bytes.len())
+        .rev()
+        .find(|&i| bytes[i] & 0xC0 != 0x80);
+    match start.and_then(|start| Some((start, decode_char(&bytes[start..])?))) {
+        Some((start, (c, size))) if start + size == bytes.len() => Some((c, size)),
+        _ => Some((char::REPLACEMENT_CHARACTER, 1)),
+    }
+}
+
+/// FIND_BYTE: posicion de la primera aparicion de `needle`
+pub fn find_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().position(|b| *b == needle)
+}
+
+/// RFIND_BYTE: posicion de la ultima aparicion de `needle`
+pub fn rfind_byte(needle: u8, haystack: &[u8]) -> Option<usize> {
+    haystack.iter().rposition(|b| *b == needle)
+}
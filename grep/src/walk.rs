@@ -0,0 +1,195 @@
+use crate::glob::Glob;
+use crate::ignore::IgnoreStack;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// Opciones del recorrido de directorios
+#[derive(Debug, Clone, Default)]
+pub struct WalkOptions {
+    pub follow_links: bool, // Seguir los enlaces simbolicos que aparecen al recorrer (-R)
+    pub max_depth: Option<usize>, // 0 son solo las rutas indicadas
+    pub include: Vec<Glob>, // Si hay alguno, solo se buscan los archivos que matcheen
+    pub exclude: Vec<Glob>,
+    pub exclude_dir: Vec<Glob>,
+    pub ignore_files: bool, // Saltear lo que indiquen .gitignore, .ignore y .git/info/exclude
+    pub hidden: bool,       // Recorrer tambien los archivos y directorios que empiezan con '.'
+}
+
+impl WalkOptions {
+    /// SELECTS_FILE: indica si un archivo pasa los filtros --include y --exclude
+    pub fn selects_file(&self, path: &Path) -> bool {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|glob| glob.matches_path(path));
+        included && !self.exclude.iter().any(|glob| glob.matches_path(path))
+    }
+
+    /// SELECTS_DIR: indica si se entra en un directorio encontrado al recorrer
+    pub fn selects_dir(&self, path: &Path) -> bool {
+        !self.exclude_dir.iter().any(|glob| glob.matches_path(path))
+    }
+}
+
+/// Problema con una ruta del recorrido. No corta el recorrido del resto
+#[derive(Debug)]
+pub struct WalkError {
+    pub path: PathBuf,
+    pub kind: WalkErrorKind,
+}
+
+#[derive(Debug)]
+pub enum WalkErrorKind {
+    Io(io::Error),
+    /// Un enlace que lleva a un directorio que ya se esta recorriendo
+    Loop,
+}
+
+impl fmt::Display for WalkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            WalkErrorKind::Io(err) => write!(f, "{}", err),
+            WalkErrorKind::Loop => write!(f, "recursive directory loop"),
+        }
+    }
+}
+
+impl std::error::Error for WalkError {}
+
+/// Recorrido en profundidad de un directorio que devuelve los archivos en orden alfabetico.
+/// La ruta inicial siempre se sigue aunque sea un enlace, como en GNU grep, y nunca se ignora
+pub struct Walk<'a> {
+    options: &'a WalkOptions,
+    stack: Vec<Pending>,
+}
+
+struct Pending {
+    path: PathBuf,
+    absolute: PathBuf, // Ruta absoluta sin resolver los enlaces de adentro del recorrido
+    depth: usize,
+    ancestors: Rc<Vec<PathBuf>>, // Rutas canonicas de los directorios que la contienen
+    ignores: Option<Rc<IgnoreStack>>, // Reglas del directorio que la contiene
+}
+
+impl<'a> Walk<'a> {
+    pub fn new(root: impl Into<PathBuf>, options: &'a WalkOptions) -> Self {
+        let path = root.into();
+        let absolute = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        Walk {
+            options,
+            stack: vec![Pending {
+                path,
+                absolute,
+                depth: 0,
+                ancestors: Rc::new(vec![]),
+                ignores: None,
+            }],
+        }
+    }
+
+    /// VISIT_DIR: agrega el contenido del directorio a la pila, o devuelve por que no se puede
+    fn visit_dir(&mut self, pending: &Pending) -> Result<(), WalkErrorKind> {
+        if self
+            .options
+            .max_depth
+            .is_some_and(|max| pending.depth >= max)
+        {
+            return Ok(());
+        }
+
+        let canonical = fs::canonicalize(&pending.path).map_err(WalkErrorKind::Io)?;
+        if pending.ancestors.contains(&canonical) {
+            return Err(WalkErrorKind::Loop);
+        }
+
+        let mut entries = fs::read_dir(&pending.path)
+            .and_then(|entries| entries.collect::<Result<Vec<_>, _>>())
+            .map_err(WalkErrorKind::Io)?;
+        entries.sort_by_key(|entry| entry.file_name());
+
+        let mut ancestors = pending.ancestors.as_ref().clone();
+        ancestors.push(canonical);
+        let ancestors = Rc::new(ancestors);
+
+        let ignores = match (&pending.ignores, self.options.ignore_files) {
+            (_, false) => None,
+            (None, true) => Some(IgnoreStack::for_root(&pending.absolute)),
+            (Some(parent), true) => Some(parent.child(&pending.absolute)),
+        };
+
+        // Al reves, para que el primero quede arriba de la pila
+        for entry in entries.into_iter().rev() {
+            self.stack.push(Pending {
+                path: entry.path(),
+                absolute: pending.absolute.join(entry.file_name()),
+                depth: pending.depth + 1,
+                ancestors: Rc::clone(&ancestors),
+                ignores: ignores.clone(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = Result<PathBuf, WalkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(pending) = self.stack.pop() {
+            let is_root = pending.depth == 0;
+            if !is_root && !self.options.hidden && is_hidden(&pending.path) {
+                continue;
+            }
+
+            let metadata = match fs::symlink_metadata(&pending.path) {
+                Ok(metadata) if metadata.file_type().is_symlink() => {
+                    if !is_root && !self.options.follow_links {
+                        continue;
+                    }
+                    fs::metadata(&pending.path)
+                }
+                other => other,
+            };
+
+            let error = |kind| {
+                Some(Err(WalkError {
+                    path: pending.path.clone(),
+                    kind,
+                }))
+            };
+
+            if let (Ok(metadata), Some(ignores)) = (&metadata, &pending.ignores) {
+                if ignores.is_ignored(&pending.absolute, metadata.is_dir()) {
+                    continue;
+                }
+            }
+
+            match metadata {
+                Err(err) => return error(WalkErrorKind::Io(err)),
+                Ok(metadata) if metadata.is_dir() => {
+                    if !is_root && !self.options.selects_dir(&pending.path) {
+                        continue;
+                    }
+                    if let Err(kind) = self.visit_dir(&pending) {
+                        return error(kind);
+                    }
+                }
+                Ok(_) => {
+                    if self.options.selects_file(&pending.path) {
+                        return Some(Ok(pending.path));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// IS_HIDDEN: los archivos y directorios ocultos son los que empiezan con '.'
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
@@ -0,0 +1,760 @@
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+/// Crea un directorio temporal propio de cada test con los archivos indicados
+fn fixture(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("grep-cli-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (path, contents) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+    dir
+}
+
+fn grep(dir: &PathBuf, args: &[&str], stdin: &str) -> Output {
+    // Con varios hilos el orden de los archivos depende de cual termina primero
+    let mut child = Command::new(env!("CARGO_BIN_EXE_grep"))
+        .arg("--sort=path")
+        .args(args)
+        .current_dir(dir)
+        .env_remove("GREP_COLORS")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+#[test]
+fn test_single_file_has_no_prefix() {
+    let dir = fixture("single", &[("a.txt", "hola\nmundo\n")]);
+
+    let output = grep(&dir, &["hola", "a.txt"], "");
+
+    assert_eq!(stdout(&output), "hola\n");
+}
+
+#[test]
+fn test_multiple_files_have_prefix() {
+    let dir = fixture(
+        "multiple",
+        &[("a.txt", "hola\nmundo\n"), ("b.txt", "hola mundo\n")],
+    );
+
+    let output = grep(&dir, &["mundo", "a.txt", "b.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:mundo\nb.txt:hola mundo\n");
+
+    let output = grep(&dir, &["-h", "mundo", "a.txt", "b.txt"], "");
+    assert_eq!(stdout(&output), "mundo\nhola mundo\n");
+}
+
+#[test]
+fn test_stdin() {
+    let dir = fixture("stdin", &[("a.txt", "hola\n")]);
+
+    let output = grep(&dir, &["b"], "abc\nxyz\nbcd\n");
+    assert_eq!(stdout(&output), "abc\nbcd\n");
+
+    let output = grep(&dir, &["-H", "b", "-"], "abc\n");
+    assert_eq!(stdout(&output), "(standard input):abc\n");
+
+    let output = grep(&dir, &["--label=in", "o", "a.txt", "-"], "foo\n");
+    assert_eq!(stdout(&output), "a.txt:hola\nin:foo\n");
+}
+
+#[test]
+fn test_missing_file_does_not_stop_search() {
+    let dir = fixture("missing", &[("a.txt", "hola\n")]);
+
+    let output = grep(&dir, &["hola", "nope.txt", "a.txt"], "");
+
+    assert_eq!(stdout(&output), "a.txt:hola\n");
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with("grep: nope.txt: "));
+}
+
+#[test]
+fn test_recursive() {
+    let dir = fixture(
+        "recursive",
+        &[
+            ("a.txt", "hola\n"),
+            ("sub/b.txt", "hola\n"),
+            ("sub/c.rs", "hola\n"),
+            ("sub/deep/d.txt", "hola\n"),
+            ("target/e.txt", "hola\n"),
+        ],
+    );
+
+    let output = grep(&dir, &["-r", "hola"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:hola\nsub/b.txt:hola\nsub/c.rs:hola\nsub/deep/d.txt:hola\ntarget/e.txt:hola\n"
+    );
+
+    let output = grep(&dir, &["-r", "hola", "sub"], "");
+    assert_eq!(
+        stdout(&output),
+        "sub/b.txt:hola\nsub/c.rs:hola\nsub/deep/d.txt:hola\n"
+    );
+
+    let output = grep(
+        &dir,
+        &["-r", "--include=*.txt", "--exclude-dir=target", "hola"],
+        "",
+    );
+    assert_eq!(
+        stdout(&output),
+        "a.txt:hola\nsub/b.txt:hola\nsub/deep/d.txt:hola\n"
+    );
+
+    let output = grep(
+        &dir,
+        &["-r", "--exclude=*.txt", "--max-depth=1", "hola"],
+        "",
+    );
+    assert_eq!(stdout(&output), "");
+
+    let output = grep(
+        &dir,
+        &["-r", "--max-depth=2", "--exclude=*.rs", "hola", "."],
+        "",
+    );
+    assert_eq!(
+        stdout(&output),
+        "./a.txt:hola\n./sub/b.txt:hola\n./target/e.txt:hola\n"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_recursive_symlinks() {
+    let dir = fixture("symlinks", &[("sub/a.txt", "hola\n")]);
+    std::os::unix::fs::symlink("..", dir.join("sub/up")).unwrap();
+    std::os::unix::fs::symlink("sub/a.txt", dir.join("link.txt")).unwrap();
+
+    // -r no sigue los enlaces que encuentra al recorrer
+    let output = grep(&dir, &["-r", "hola"], "");
+    assert_eq!(stdout(&output), "sub/a.txt:hola\n");
+
+    // -R los sigue, y el que vuelve a un directorio ya visitado se informa como un ciclo
+    let output = grep(&dir, &["-R", "hola"], "");
+    assert_eq!(stdout(&output), "link.txt:hola\nsub/a.txt:hola\n");
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "grep: sub/up: recursive directory loop\n"
+    );
+}
+
+#[test]
+fn test_recursive_ignore_files() {
+    let dir = fixture(
+        "ignore",
+        &[
+            (".git/HEAD", "hola\n"),
+            (".git/info/exclude", "*.tmp\n"),
+            (".gitignore", "target/\n*.log\n/root.txt\n"),
+            (".hidden.txt", "hola\n"),
+            ("a.txt", "hola\n"),
+            ("a.log", "hola\n"),
+            ("a.tmp", "hola\n"),
+            ("root.txt", "hola\n"),
+            ("sub/root.txt", "hola\n"),
+            ("sub/.ignore", "!keep.log\n"),
+            ("sub/keep.log", "hola\n"),
+            ("sub/other.log", "hola\n"),
+            ("target/b.txt", "hola\n"),
+        ],
+    );
+
+    let output = grep(&dir, &["-r", "hola"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:hola\nsub/keep.log:hola\nsub/root.txt:hola\n"
+    );
+
+    // Las reglas de los directorios de arriba valen al buscar en uno de adentro
+    let output = grep(&dir, &["-r", "hola", "sub"], "");
+    assert_eq!(stdout(&output), "sub/keep.log:hola\nsub/root.txt:hola\n");
+
+    let output = grep(&dir, &["-r", "--hidden", "--exclude-dir=.git", "hola"], "");
+    assert_eq!(
+        stdout(&output),
+        ".hidden.txt:hola\na.txt:hola\nsub/keep.log:hola\nsub/root.txt:hola\n"
+    );
+
+    let output = grep(&dir, &["-r", "--no-ignore", "hola"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.log:hola\na.tmp:hola\na.txt:hola\nroot.txt:hola\nsub/keep.log:hola\n\
+         sub/other.log:hola\nsub/root.txt:hola\ntarget/b.txt:hola\n"
+    );
+
+    // Un archivo indicado explicitamente se busca aunque este ignorado
+    let output = grep(&dir, &["-r", "hola", "a.log"], "");
+    assert_eq!(stdout(&output), "a.log:hola\n");
+    // Un .ignore de arriba gana sobre un .gitignore de un directorio de adentro
+    let dir = fixture(
+        "ignore-precedence",
+        &[
+            (".git/HEAD", "hola\n"),
+            (".ignore", "!*.log\n"),
+            ("child/.gitignore", "*.log\n*.tmp\n"),
+            ("child/x.log", "hola\n"),
+            ("child/x.tmp", "hola\n"),
+        ],
+    );
+    let output = grep(&dir, &["-r", "hola"], "");
+    assert_eq!(stdout(&output), "child/x.log:hola\n");
+}
+
+#[test]
+fn test_parallel_output() {
+    // Cada archivo escribe mas que una parte de la salida de un hilo (64 KiB)
+    let files: Vec<(String, String)> = (0..12)
+        .map(|i| {
+            let lines: String = (0..8000).map(|j| format!("linea {} {}\n", i, j)).collect();
+            (format!("f{:02}.txt", i), lines)
+        })
+        .collect();
+    let files: Vec<(&str, &str)> = files.iter().map(|(p, c)| (&p[..], &c[..])).collect();
+    let dir = fixture("parallel", &files);
+
+    let sequential = stdout(&grep(&dir, &["-r", "-j1", "linea"], ""));
+    let parallel = stdout(&grep(&dir, &["-r", "--threads=8", "linea"], ""));
+    assert_eq!(sequential.lines().count(), 12 * 8000);
+    assert_eq!(parallel, sequential);
+
+    // Sin orden fijo, las lineas de cada archivo siguen juntas
+    let output = Command::new(env!("CARGO_BIN_EXE_grep"))
+        .args(["-r", "-j8", "linea"])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let unordered = stdout(&output);
+    let mut names: Vec<&str> = unordered
+        .lines()
+        .map(|l| l.split(':').next().unwrap())
+        .collect();
+    names.dedup();
+    assert_eq!(names.len(), 12);
+}
+
+#[test]
+fn test_multiline() {
+    let dir = fixture("multiline", &[("a.txt", "ab\ncd\nef\n")]);
+
+    let output = grep(&dir, &["b[[:space:]]c", "a.txt"], "");
+    assert_eq!(stdout(&output), "");
+
+    let output = grep(&dir, &["-UH", "b[[:space:]]c", "a.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:ab\na.txt:cd\n");
+}
+
+#[test]
+fn test_match_search_stops_at_line_end() {
+    // Si '.' pasara al '\n', cada busqueda recorreria el resto del bloque: con 3000 lineas eso
+    // lleva segundos en lugar de milisegundos
+    let lines: String = (1..=3000)
+        .map(|i| format!("a xxxxxxxxxxxx {}\n", i))
+        .collect();
+    let dir = fixture("line-end", &[("a.txt", &lines)]);
+
+    let started = std::time::Instant::now();
+    let output = grep(&dir, &["-c", "a.*9$", "a.txt"], "");
+    assert_eq!(stdout(&output), "300\n");
+    assert!(started.elapsed() < std::time::Duration::from_secs(2));
+
+    let output = grep(&dir, &["-c", "[^b]*9$", "a.txt"], "");
+    assert_eq!(stdout(&output), "300\n");
+}
+
+#[test]
+fn test_mmap_modes() {
+    let dir = fixture("mmap", &[("a.txt", "uno\ndos\ntres"), ("empty.txt", "")]);
+
+    for mode in ["--mmap", "--no-mmap"] {
+        let output = grep(&dir, &[mode, "o", "a.txt", "empty.txt"], "");
+        assert_eq!(stdout(&output), "a.txt:uno\na.txt:dos\n");
+
+        let output = grep(&dir, &[mode, "es$", "a.txt", "-"], "mes");
+        assert_eq!(stdout(&output), "a.txt:tres\n(standard input):mes\n");
+    }
+}
+
+#[test]
+fn test_invert_count_quiet_max() {
+    let dir = fixture(
+        "selection",
+        &[("a.txt", "uno\ndos\ntres\ncuatro\n"), ("b.txt", "x\n")],
+    );
+
+    let output = grep(&dir, &["-v", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "tres\n");
+
+    let output = grep(&dir, &["-c", "o", "a.txt", "b.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:3\nb.txt:0\n");
+
+    let output = grep(&dir, &["-cv", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "1\n");
+
+    let output = grep(&dir, &["-m2", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "uno\ndos\n");
+
+    let output = grep(&dir, &["-m2", "-c", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "2\n");
+
+    let output = grep(&dir, &["-m0", "-c", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "0\n");
+
+    let output = grep(&dir, &["-m1", "-v", "o", "a.txt", "b.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:tres\nb.txt:x\n");
+
+    let output = grep(&dir, &["-q", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = grep(&dir, &["-qc", "o", "b.txt", "a.txt"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_files_with_and_without_match() {
+    let dir = fixture(
+        "file_names",
+        &[
+            ("a.txt", "uno\ndos\n"),
+            ("b.txt", "tres\n"),
+            ("c.txt", "dos\n"),
+        ],
+    );
+
+    let output = grep(&dir, &["-l", "o", "a.txt", "b.txt", "c.txt"], "");
+    assert_eq!(stdout(&output), "a.txt\nc.txt\n");
+
+    let output = grep(&dir, &["-L", "o", "a.txt", "b.txt", "c.txt"], "");
+    assert_eq!(stdout(&output), "b.txt\n");
+
+    let output = grep(&dir, &["-lZ", "o", "a.txt", "b.txt", "c.txt"], "");
+    assert_eq!(stdout(&output), "a.txt\0c.txt\0");
+
+    // -l manda sobre -c, y con un solo archivo tambien muestra el nombre
+    let output = grep(&dir, &["-lc", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "a.txt\n");
+
+    let output = grep(&dir, &["-l", "--label=in", "x", "-"], "x\n");
+    assert_eq!(stdout(&output), "in\n");
+
+    // -Z tambien cambia el separador de los prefijos
+    let output = grep(&dir, &["-Z", "dos", "a.txt", "c.txt"], "");
+    assert_eq!(stdout(&output), "a.txt\0dos\nc.txt\0dos\n");
+    let output = grep(&dir, &["-cZ", "dos", "a.txt", "b.txt"], "");
+    assert_eq!(stdout(&output), "a.txt\x001\nb.txt\x000\n");
+}
+
+#[test]
+fn test_line_numbers_offsets_and_columns() {
+    let dir = fixture("locations", &[("a.txt", "uno\ndos\ntres\ncuatro o\n")]);
+
+    let output = grep(&dir, &["-n", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "1:uno\n2:dos\n4:cuatro o\n");
+
+    let output = grep(&dir, &["-Hnb", "--column", "o", "a.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:1:3:0:uno\na.txt:2:2:4:dos\na.txt:4:6:13:cuatro o\n"
+    );
+
+    let output = grep(&dir, &["-nv", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "3:tres\n");
+
+    let output = grep(&dir, &["--vimgrep", "o", "a.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:1:3:uno\na.txt:2:2:dos\na.txt:4:6:cuatro o\na.txt:4:8:cuatro o\n"
+    );
+
+    let output = grep(&dir, &["-nUb", "s[[:space:]]c"], "tres\ncuatro\n");
+    assert_eq!(stdout(&output), "1:0:tres\n2:5:cuatro\n");
+}
+
+#[test]
+fn test_context_lines() {
+    let dir = fixture(
+        "context",
+        &[("a.txt", "uno\ndos\ntres\ncuatro\ncinco\nseis\n")],
+    );
+
+    let output = grep(
+        &dir,
+        &["-n", "-A1", "-e", "uno", "-e", "cinco", "a.txt"],
+        "",
+    );
+    assert_eq!(stdout(&output), "1:uno\n2-dos\n--\n5:cinco\n6-seis\n");
+
+    // Los resultados de archivos distintos tambien se separan
+    fs::write(dir.join("b.txt"), "siete\nocho\n").unwrap();
+    let output = grep(
+        &dir,
+        &["-A1", "-e", "seis", "-e", "siete", "a.txt", "b.txt"],
+        "",
+    );
+    assert_eq!(stdout(&output), "a.txt:seis\n--\nb.txt:siete\nb.txt-ocho\n");
+
+    let output = grep(&dir, &["-Hb", "-B1", "tres", "a.txt"], "");
+    assert_eq!(stdout(&output), "a.txt-4-dos\na.txt:8:tres\n");
+
+    // Con -v, el contexto son las lineas que matchean
+    let output = grep(&dir, &["-v", "-C1", "[ou]"], "a\nuno\ndos\ncuatro\nb\n");
+    assert_eq!(stdout(&output), "a\nuno\n--\ncuatro\nb\n");
+
+    let output = grep(&dir, &["-n", "-m1", "-A2", "o"], "uno\ndos\ntres\n");
+    assert_eq!(stdout(&output), "1:uno\n2-dos\n3-tres\n");
+
+    let output = grep(
+        &dir,
+        &[
+            "-A0",
+            "--group-separator=**",
+            "-e",
+            "uno",
+            "-e",
+            "tres",
+            "a.txt",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "uno\n**\ntres\n");
+
+    let output = grep(
+        &dir,
+        &[
+            "-A0",
+            "--no-group-separator",
+            "-e",
+            "uno",
+            "-e",
+            "tres",
+            "a.txt",
+        ],
+        "",
+    );
+    assert_eq!(stdout(&output), "uno\ntres\n");
+}
+
+#[test]
+fn test_only_matching() {
+    let dir = fixture("only-matching", &[("a.log", "id=17 id=4\nnada\nid=256\n")]);
+
+    let output = grep(&dir, &["-o", "[0-9]+", "a.log"], "");
+    assert_eq!(stdout(&output), "17\n4\n256\n");
+
+    let output = grep(&dir, &["-Hnbo", "id=[0-9]+", "a.log"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.log:1:0:id=17\na.log:1:6:id=4\na.log:3:16:id=256\n"
+    );
+
+    // Los matches vacios no se escriben, pero la linea igual cuenta como seleccionada
+    let output = grep(&dir, &["-o", "x*", "a.log"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+
+    let output = grep(&dir, &["-o", "-C1", "nada", "a.log"], "");
+    assert_eq!(stdout(&output), "nada\n");
+
+    // Sin las lineas de contexto, los grupos igual se separan
+    let output = grep(&dir, &["-o", "-A0", "id=[0-9]+", "a.log"], "");
+    assert_eq!(stdout(&output), "id=17\nid=4\n--\nid=256\n");
+
+    let output = grep(&dir, &["-onU", "7 id=4[[:space:]]n"], "id=17 id=4\nnada\n");
+    assert_eq!(stdout(&output), "1:7 id=4\n2:n\n");
+}
+
+#[test]
+fn test_colors() {
+    let dir = fixture("colors", &[("a.txt", "uno dos\ntres\n")]);
+
+    let output = grep(&dir, &["--color=always", "-Hn", "o", "a.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "\x1b[35m\x1b[Ka.txt\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K\
+         \x1b[32m\x1b[K1\x1b[m\x1b[K\x1b[36m\x1b[K:\x1b[m\x1b[K\
+         un\x1b[01;31m\x1b[Ko\x1b[m\x1b[K d\x1b[01;31m\x1b[Ko\x1b[m\x1b[Ks\n"
+    );
+
+    // Con -v se resaltan los matches de las lineas de contexto
+    let output = grep(&dir, &["--color=always", "-v", "-A1", "tres", "a.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "uno dos\n\x1b[01;31m\x1b[Ktres\x1b[m\x1b[K\n"
+    );
+
+    // La salida de los tests no es una terminal
+    let output = grep(&dir, &["--color=auto", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "uno dos\n");
+    let output = grep(&dir, &["--color=never", "o", "a.txt"], "");
+    assert_eq!(stdout(&output), "uno dos\n");
+}
+
+#[test]
+fn test_word_and_line_regexp() {
+    let dir = fixture("word-line", &[("a.txt", "valid id\nvalid\nid\nid_x\n")]);
+
+    let output = grep(&dir, &["-nw", "id", "a.txt"], "");
+    assert_eq!(stdout(&output), "1:valid id\n3:id\n");
+
+    let output = grep(&dir, &["-ow", "[a-z]+", "a.txt"], "");
+    assert_eq!(stdout(&output), "valid\nid\nvalid\nid\n");
+
+    let output = grep(&dir, &["-x", "-e", "id", "-e", "valid", "a.txt"], "");
+    assert_eq!(stdout(&output), "valid\nid\n");
+
+    let output = grep(&dir, &["-Fx", "id", "a.txt"], "");
+    assert_eq!(stdout(&output), "id\n");
+}
+
+#[test]
+fn test_ignore_case() {
+    let dir = fixture(
+        "ignore-case",
+        &[("a.txt", "Hello World\nhello\nHELLO.x\nnope\n")],
+    );
+
+    let output = grep(&dir, &["-i", "hello", "a.txt"], "");
+    assert_eq!(stdout(&output), "Hello World\nhello\nHELLO.x\n");
+
+    let output = grep(&dir, &["-inr", "h[a-f]llo w"], "");
+    assert_eq!(stdout(&output), "a.txt:1:Hello World\n");
+
+    let output = grep(&dir, &["-iF", "hello.X", "a.txt"], "");
+    assert_eq!(stdout(&output), "HELLO.x\n");
+
+    let output = grep(&dir, &["-io", "[[:upper:]]ope", "a.txt"], "");
+    assert_eq!(stdout(&output), "nope\n");
+}
+
+#[test]
+fn test_multiple_patterns_and_pattern_files() {
+    let dir = fixture(
+        "pattern-files",
+        &[
+            ("a.txt", "uno\ndos\ntres\n"),
+            ("lista.txt", "^u\ns$\n"),
+            ("vacia.txt", ""),
+            ("todo.txt", "x\n\n"),
+            ("mala.txt", "uno\n(dos\n"),
+        ],
+    );
+
+    let output = grep(&dir, &["-e", "^d", "-e", "tr", "a.txt"], "");
+    assert_eq!(stdout(&output), "dos\ntres\n");
+
+    let output = grep(&dir, &["-f", "lista.txt", "a.txt"], "");
+    assert_eq!(stdout(&output), "uno\ndos\ntres\n");
+
+    let output = grep(&dir, &["-f", "vacia.txt", "-e", "tres", "a.txt"], "");
+    assert_eq!(stdout(&output), "tres\n");
+
+    // Una linea vacia en el archivo de patrones matchea todas las lineas
+    let output = grep(&dir, &["-c", "-f", "todo.txt", "a.txt"], "");
+    assert_eq!(stdout(&output), "3\n");
+
+    let output = grep(&dir, &["-f", "-", "a.txt"], "^t\n");
+    assert_eq!(stdout(&output), "tres\n");
+
+    let output = grep(&dir, &["-f", "mala.txt", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(
+        String::from_utf8_lossy(&output.stderr).starts_with("grep: mala.txt:2: regex parse error")
+    );
+}
+
+#[test]
+fn test_json_output() {
+    let dir = fixture("json", &[("a.txt", "uno\ndos\n"), ("b.txt", "tres\n")]);
+
+    let output = grep(&dir, &["--json", "-A1", "u", "a.txt", "b.txt"], "");
+    let out = stdout(&output);
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(
+        lines[0],
+        r#"{"type":"begin","data":{"path":{"text":"a.txt"}}}"#
+    );
+    assert_eq!(
+        lines[1],
+        r#"{"type":"match","data":{"path":{"text":"a.txt"},"lines":{"text":"uno\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"u"},"start":0,"end":1}]}}"#
+    );
+    assert!(lines[2].starts_with(r#"{"type":"context","data":{"path":{"text":"a.txt"},"lines":{"text":"dos\n"},"line_number":2,"#));
+    assert!(lines[3].starts_with(r#"{"type":"end","data":{"path":{"text":"a.txt"}"#));
+    // b.txt no tiene resultados, pero se busco
+    assert!(lines[4].starts_with(r#"{"type":"summary","data":{"elapsed_total":"#));
+    assert!(lines[4].contains(r#""searches":2,"searches_with_match":1,"#));
+    assert!(lines[4].ends_with(r#""matched_lines":1,"matches":1}}}"#));
+}
+
+#[test]
+fn test_format_template() {
+    let dir = fixture(
+        "format",
+        &[("a.txt", "id=12 x\nnada\nid=7 id=8\n"), ("b.txt", "id=3\n")],
+    );
+
+    let template = "{path}:{line}:{col}:{byte}\\t{match} [{1}] <{num}>";
+    let pattern = "id=(?P<num>[0-9]+)";
+    let output = grep(&dir, &["--format", template, pattern, "a.txt", "b.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:1:1:0\tid=12 [12] <12>\n\
+         a.txt:3:1:13\tid=7 [7] <7>\n\
+         a.txt:3:6:18\tid=8 [8] <8>\n\
+         b.txt:1:1:0\tid=3 [3] <3>\n"
+    );
+
+    // Sin contexto ni separadores; con -v un registro por linea
+    let output = grep(
+        &dir,
+        &["--format={line}|{text}", "-C1", "-v", "id", "a.txt"],
+        "",
+    );
+    assert_eq!(stdout(&output), "2|nada\n");
+
+    // -c gana sobre la plantilla
+    let output = grep(&dir, &["-c", "--format={match}", "id", "a.txt"], "");
+    assert_eq!(stdout(&output), "2\n");
+
+    let output = grep(&dir, &["--format={2}", "id=([0-9])", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown placeholder '{2}'"));
+}
+
+#[test]
+fn test_exit_status_and_messages() {
+    let dir = fixture("exit-status", &[("a.txt", "uno\ndos\n")]);
+    let stderr = |output: &Output| String::from_utf8_lossy(&output.stderr).to_string();
+
+    let output = grep(&dir, &["uno", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(0));
+    let output = grep(&dir, &["tres", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stderr(&output), "");
+
+    // La seleccion decide, no la salida: -L sin nombres tambien es exito
+    let output = grep(&dir, &["-L", "uno", "a.txt"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(0));
+
+    // Un error da 2 aunque haya matches en otro archivo, salvo con -q
+    let output = grep(&dir, &["uno", "nope.txt", "a.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:uno\n");
+    // El mensaje es el del sistema, sin el "(os error N)"
+    assert_eq!(
+        stderr(&output),
+        "grep: nope.txt: No such file or directory\n"
+    );
+    assert_eq!(output.status.code(), Some(2));
+    let output = grep(&dir, &["-q", "uno", "nope.txt", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(0));
+    let output = grep(&dir, &["-q", "tres", "nope.txt", "a.txt"], "");
+    assert_eq!(output.status.code(), Some(2));
+
+    // -s calla los errores de los archivos, pero no cambia el estado
+    let output = grep(&dir, &["-s", "uno", "nope.txt", "a.txt"], "");
+    assert_eq!(stderr(&output), "");
+    assert_eq!(output.status.code(), Some(2));
+    let output = grep(&dir, &["-s", "-r", "uno", "nope"], "");
+    assert_eq!(stderr(&output), "");
+    assert_eq!(output.status.code(), Some(2));
+
+    // Los errores del patron y de las opciones se informan siempre
+    let output = grep(&dir, &["-s", "a[", "a.txt"], "");
+    assert!(stderr(&output).starts_with("grep: regex parse error:"));
+    assert_eq!(output.status.code(), Some(2));
+    let output = grep(&dir, &["-s", "--bogus", "a.txt"], "");
+    assert!(stderr(&output).starts_with("grep: unrecognized option '--bogus'"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn test_closed_output() {
+    use std::io::Read;
+
+    let lines: String = (1..=200_000).map(|i| format!("{}\n", i)).collect();
+    let dir = fixture("closed-output", &[("a.txt", &lines)]);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_grep"))
+        .args(["1", "a.txt"])
+        .current_dir(&dir)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // Como `grep 1 a.txt | head -c 1`: la salida se cierra antes de que termine
+    let mut first = [0; 1];
+    child.stdout.take().unwrap().read_exact(&mut first).unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert_eq!(&first, b"1");
+    assert_eq!(String::from_utf8_lossy(&output.stderr), "");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn test_binary_files() {
+    let dir = fixture(
+        "binary-files",
+        &[
+            ("a.txt", "uno\ndos\n"),
+            ("nul.bin", "uno\nd\0s\ntres\n"),
+            ("latin1.txt", ""),
+        ],
+    );
+    fs::write(dir.join("latin1.txt"), b"uno\nca\xF1a\n").unwrap();
+
+    let output = grep(&dir, &["uno", "a.txt", "nul.bin", "latin1.txt"], "");
+    assert_eq!(
+        stdout(&output),
+        "a.txt:uno\nBinary file nul.bin matches\nBinary file latin1.txt matches\n"
+    );
+    assert_eq!(output.status.code(), Some(0));
+
+    // El contexto de un archivo binario tampoco se escribe
+    let output = grep(&dir, &["-n", "-C1", "tres", "nul.bin"], "");
+    assert_eq!(stdout(&output), "Binary file nul.bin matches\n");
+
+    // -c y -l cuentan los matches igual
+    let output = grep(&dir, &["-c", "uno", "nul.bin"], "");
+    assert_eq!(stdout(&output), "1\n");
+    let output = grep(&dir, &["-l", "uno", "nul.bin"], "");
+    assert_eq!(stdout(&output), "nul.bin\n");
+
+    let output = grep(&dir, &["-a", "-n", "s$", "nul.bin"], "");
+    assert_eq!(stdout(&output), "2:d\0s\n3:tres\n");
+    let output = grep(&dir, &["--binary-files=text", "tres", "nul.bin"], "");
+    assert_eq!(stdout(&output), "tres\n");
+
+    // Con -I los archivos binarios no matchean
+    let output = grep(&dir, &["-I", "uno", "a.txt", "nul.bin", "latin1.txt"], "");
+    assert_eq!(stdout(&output), "a.txt:uno\n");
+    let output = grep(&dir, &["-I", "uno", "nul.bin"], "");
+    assert_eq!(stdout(&output), "");
+    assert_eq!(output.status.code(), Some(1));
+    let output = grep(&dir, &["-I", "-L", "uno", "a.txt", "nul.bin"], "");
+    assert_eq!(stdout(&output), "nul.bin\n");
+}
@@ -0,0 +1,69 @@
+use grep::color::Colors;
+
+fn painted(colors: &Colors, sgr: &str, text: &str) -> String {
+    let mut out = vec![];
+    colors.paint(&mut out, sgr, text.as_bytes()).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_default_colors() {
+    let colors = Colors::parse("");
+
+    assert_eq!(colors, Colors::default());
+    assert_eq!(colors.selected_match, "01;31");
+    assert_eq!(colors.file_name, "35");
+    assert_eq!(colors.separator, "36");
+    assert!(colors.selected_line.is_empty());
+}
+
+#[test]
+fn test_parse_grep_colors() {
+    let colors = Colors::parse("ms=04;32:mc=1:fn=:ln=33:bn=34:se=0:sl=2:cx=7:ne");
+    assert_eq!(colors.selected_match, "04;32");
+    assert_eq!(colors.context_match, "1");
+    assert_eq!(colors.file_name, "");
+    assert_eq!(colors.line_number, "33");
+    assert_eq!(colors.byte_offset, "34");
+    assert_eq!(colors.separator, "0");
+    assert_eq!(colors.line_colors(false), ("2", "7"));
+    assert!(colors.no_erase);
+
+    // mt cambia los dos colores de los matches
+    let colors = Colors::parse("mt=35");
+    assert_eq!(
+        (
+            colors.selected_match.as_str(),
+            colors.context_match.as_str()
+        ),
+        ("35", "35")
+    );
+
+    // Con rv, -v intercambia los colores de las lineas
+    let colors = Colors::parse("rv:sl=1:cx=2");
+    assert_eq!(colors.line_colors(false), ("1", "2"));
+    assert_eq!(colors.line_colors(true), ("2", "1"));
+}
+
+#[test]
+fn test_invalid_entries_are_ignored() {
+    let colors = Colors::parse("ms=rojo:xx=1:fn=36::ln");
+
+    assert_eq!(colors.selected_match, "01;31");
+    assert_eq!(colors.file_name, "36");
+    assert_eq!(colors.line_number, "32");
+}
+
+#[test]
+fn test_paint() {
+    let colors = Colors::default();
+    assert_eq!(
+        painted(&colors, "01;31", "uno"),
+        "\x1b[01;31m\x1b[Kuno\x1b[m\x1b[K"
+    );
+    assert_eq!(painted(&colors, "", "uno"), "uno");
+    assert_eq!(painted(&colors, "01;31", ""), "");
+
+    let colors = Colors::parse("ne");
+    assert_eq!(painted(&colors, "32", "7"), "\x1b[32m7\x1b[m");
+}
@@ -0,0 +1,345 @@
+use grep::config::{parse_args, BinaryFiles, ColorChoice, Command, Config, ConfigError, SortBy};
+
+fn search(args: &[&str]) -> Config {
+    match parse_args(args.iter().copied()) {
+        Ok(Command::Search(config)) => *config,
+        other => panic!("expected a search, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_pattern_and_file() {
+    let config = search(&["ab.c", "file.txt"]);
+
+    assert_eq!(config.patterns, vec!["ab.c"]);
+    assert_eq!(config.paths, vec!["file.txt"]);
+    assert!(!config.fixed_strings);
+}
+
+#[test]
+fn test_bundled_flags_with_value() {
+    let bundled = search(&["-Fe", "a.b", "file.txt"]);
+    let attached = search(&["-Fea.b", "file.txt"]);
+    let separate = search(&["-F", "-e", "a.b", "file.txt"]);
+
+    assert_eq!(bundled, separate);
+    assert_eq!(attached, separate);
+    assert!(separate.fixed_strings);
+    assert_eq!(separate.patterns, vec!["a.b"]);
+}
+
+#[test]
+fn test_long_options() {
+    let config = search(&[
+        "--regexp=one",
+        "--regexp",
+        "two",
+        "--file=patterns.txt",
+        "--fixed",
+        "file.txt",
+    ]);
+
+    assert_eq!(config.patterns, vec!["one", "two"]);
+    assert_eq!(config.pattern_files, vec!["patterns.txt"]);
+    assert!(config.fixed_strings);
+    assert_eq!(config.paths, vec!["file.txt"]);
+}
+
+#[test]
+fn test_double_dash_terminator() {
+    let config = search(&["--", "-F", "-e"]);
+
+    assert_eq!(config.patterns, vec!["-F"]);
+    assert_eq!(config.paths, vec!["-e"]);
+    assert!(!config.fixed_strings);
+}
+
+#[test]
+fn test_help_and_version() {
+    assert_eq!(parse_args(["--help", "--bogus"]), Ok(Command::Help));
+    assert_eq!(parse_args(["-V"]), Ok(Command::Version));
+    assert_eq!(parse_args(["--vers"]), Ok(Command::Version));
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        parse_args(["-k", "a", "file.txt"]),
+        Err(ConfigError::UnknownShortOption('k'))
+    );
+    assert_eq!(
+        parse_args(["--bogus", "a", "file.txt"]),
+        Err(ConfigError::UnknownLongOption("--bogus".to_string()))
+    );
+    assert_eq!(
+        parse_args(["file.txt", "-e"]),
+        Err(ConfigError::MissingValue("e".to_string()))
+    );
+    assert_eq!(
+        parse_args(["file.txt", "--regexp"]),
+        Err(ConfigError::MissingValue("--regexp".to_string()))
+    );
+    assert_eq!(
+        ConfigError::MissingValue("e".to_string()).to_string(),
+        "option requires an argument -- 'e'"
+    );
+    assert_eq!(
+        ConfigError::MissingValue("--regexp".to_string()).to_string(),
+        "option '--regexp' requires an argument"
+    );
+    assert_eq!(
+        parse_args(["--help=yes"]),
+        Err(ConfigError::UnexpectedValue("help".to_string()))
+    );
+    assert!(matches!(
+        parse_args(["--f", "a", "file.txt"]),
+        Err(ConfigError::AmbiguousOption(..))
+    ));
+    assert_eq!(
+        parse_args(Vec::<String>::new()),
+        Err(ConfigError::MissingPattern)
+    );
+}
+
+#[test]
+fn test_files_and_filename_options() {
+    let stdin = search(&["a"]);
+    assert!(stdin.paths.is_empty());
+    assert!(!stdin.show_filename());
+    assert_eq!(stdin.display_name("-"), "(standard input)");
+
+    let several = search(&["a", "one.txt", "-", "two.txt"]);
+    assert_eq!(several.paths, vec!["one.txt", "-", "two.txt"]);
+    assert!(several.show_filename());
+
+    let suppressed = search(&["-h", "a", "one.txt", "two.txt"]);
+    assert!(!suppressed.show_filename());
+
+    let forced = search(&["-H", "--label=input", "a"]);
+    assert!(forced.show_filename());
+    assert_eq!(forced.display_name("-"), "input");
+    assert_eq!(forced.display_name("one.txt"), "one.txt");
+}
+
+#[test]
+fn test_recursive_options() {
+    let config = search(&[
+        "-r",
+        "--include=*.rs",
+        "--exclude",
+        "*.bak",
+        "--exclude-dir=target",
+        "--max-depth=2",
+        "a",
+    ]);
+    assert!(config.recursive);
+    assert!(!config.dereference);
+    assert!(config.show_filename());
+    assert_eq!(config.include, vec!["*.rs"]);
+    assert_eq!(config.exclude, vec!["*.bak"]);
+    assert_eq!(config.exclude_dir, vec!["target"]);
+    assert_eq!(config.max_depth, Some(2));
+
+    let config = search(&["-R", "a", "dir"]);
+    assert!(config.recursive && config.dereference);
+
+    assert_eq!(
+        parse_args(["--max-depth=x", "a"]),
+        Err(ConfigError::InvalidValue("max-depth", "x".to_string()))
+    );
+}
+
+#[test]
+fn test_ignore_options() {
+    let config = search(&["-r", "a"]);
+    assert!(!config.no_ignore && !config.hidden);
+
+    let config = search(&["-r", "--no-ignore", "--hidden", "a"]);
+    assert!(config.no_ignore && config.hidden);
+}
+
+#[test]
+fn test_parallel_options() {
+    let config = search(&["a"]);
+    assert_eq!(config.threads, None);
+    assert_eq!(config.sort, SortBy::None);
+
+    let config = search(&["-j4", "--sort", "path", "a"]);
+    assert_eq!(config.threads, Some(4));
+    assert_eq!(config.sort, SortBy::Path);
+
+    assert_eq!(
+        parse_args(["-j0", "a"]),
+        Err(ConfigError::InvalidValue("threads", "0".to_string()))
+    );
+    assert_eq!(
+        parse_args(["--sort=size", "a"]),
+        Err(ConfigError::InvalidValue("sort", "size".to_string()))
+    );
+}
+
+#[test]
+fn test_multiline_option() {
+    assert!(!search(&["a"]).multiline);
+    assert!(search(&["-U", "a"]).multiline);
+    assert!(search(&["--multiline", "a"]).multiline);
+}
+
+#[test]
+fn test_mmap_options() {
+    assert_eq!(search(&["a"]).mmap, None);
+    assert_eq!(search(&["--mmap", "a"]).mmap, Some(true));
+    assert_eq!(search(&["--mmap", "--no-mmap", "a"]).mmap, Some(false));
+}
+
+#[test]
+fn test_binary_files_options() {
+    assert_eq!(search(&["a"]).binary_files, BinaryFiles::Binary);
+    assert_eq!(search(&["-a", "a"]).binary_files, BinaryFiles::Text);
+    assert_eq!(search(&["--text", "a"]).binary_files, BinaryFiles::Text);
+    assert_eq!(search(&["-I", "a"]).binary_files, BinaryFiles::WithoutMatch);
+    assert_eq!(
+        search(&["--binary-files=without-match", "a"]).binary_files,
+        BinaryFiles::WithoutMatch
+    );
+    // Gana la ultima, tambien dentro de un grupo de letras
+    assert_eq!(search(&["-Ia", "a"]).binary_files, BinaryFiles::Text);
+    assert_eq!(
+        search(&["-aIn", "a"]).binary_files,
+        BinaryFiles::WithoutMatch
+    );
+    assert_eq!(
+        search(&["-I", "--binary-files", "binary", "a"]).binary_files,
+        BinaryFiles::Binary
+    );
+
+    assert_eq!(
+        parse_args(["--binary-files=data", "a"]),
+        Err(ConfigError::InvalidValue(
+            "binary-files",
+            "data".to_string()
+        ))
+    );
+}
+
+#[test]
+fn test_no_messages_option() {
+    assert!(!search(&["a"]).no_messages);
+    assert!(search(&["-s", "a"]).no_messages);
+    assert!(search(&["--no-messages", "a"]).no_messages);
+    assert!(matches!(
+        parse_args(["--no-m", "a"]),
+        Err(ConfigError::AmbiguousOption(..))
+    ));
+}
+
+#[test]
+fn test_selection_options() {
+    let config = search(&["-vcq", "-m", "3", "a"]);
+    assert!(config.invert_match && config.count && config.quiet);
+    assert_eq!(config.max_count, Some(3));
+
+    let config = search(&["--invert-match", "--count", "--quiet", "--max-count=0", "a"]);
+    assert!(config.invert_match && config.count && config.quiet);
+    assert_eq!(config.max_count, Some(0));
+
+    let config = search(&["-inr", "a"]);
+    assert!(config.ignore_case && config.line_number && config.recursive);
+    assert!(search(&["--ignore-case", "a"]).ignore_case);
+    assert!(!search(&["a"]).ignore_case);
+
+    assert_eq!(
+        parse_args(["-m", "-1", "a"]),
+        Err(ConfigError::InvalidValue("max-count", "-1".to_string()))
+    );
+}
+
+#[test]
+fn test_file_name_options() {
+    let config = search(&["-lZ", "a"]);
+    assert!(config.files_with_matches && !config.files_without_match && config.null);
+
+    // El ultimo de -l y -L es el que vale
+    let config = search(&["-l", "-L", "a"]);
+    assert!(!config.files_with_matches && config.files_without_match);
+
+    let config = search(&[
+        "--files-without-match",
+        "--files-with-matches",
+        "--null",
+        "a",
+    ]);
+    assert!(config.files_with_matches && !config.files_without_match && config.null);
+}
+
+#[test]
+fn test_line_prefix_options() {
+    let config = search(&["-nb", "--column", "a"]);
+    assert!(config.line_number && config.byte_offset && config.column);
+    assert!(!config.show_filename());
+
+    let config = search(&["-o", "--only-matching", "a"]);
+    assert!(config.only_matching);
+
+    let config = search(&["--vimgrep", "a"]);
+    assert!(config.vimgrep);
+    assert!(config.show_filename());
+    assert!(!search(&["--vimgrep", "-h", "a"]).show_filename());
+
+    // Con --json y con --format no hay separadores entre grupos
+    let config = search(&["--json", "-C1", "a"]);
+    assert!(config.json);
+    assert_eq!(config.group_separator(), None);
+    let config = search(&["--format={line}: {match}", "-C1", "a"]);
+    assert_eq!(config.format.as_deref(), Some("{line}: {match}"));
+    assert_eq!(config.group_separator(), None);
+}
+
+#[test]
+fn test_context_options() {
+    let config = search(&["-C", "2", "a"]);
+    assert_eq!((config.before_context(), config.after_context()), (2, 2));
+    assert_eq!(config.group_separator(), Some("--"));
+
+    // -A y -B tienen prioridad sobre -C, en cualquier orden
+    let config = search(&["-A1", "-C3", "--before-context=0", "a"]);
+    assert_eq!((config.before_context(), config.after_context()), (0, 1));
+
+    let config = search(&["-B0", "--group-separator=##", "a"]);
+    assert_eq!(config.group_separator(), Some("##"));
+    let config = search(&["-A1", "--no-group-separator", "a"]);
+    assert_eq!(config.group_separator(), None);
+
+    // Sin contexto, o sin lineas en la salida, no hay grupos que separar
+    assert_eq!(search(&["-c", "-A1", "a"]).group_separator(), None);
+    assert_eq!(
+        search(&["--group-separator=##", "a"]).group_separator(),
+        None
+    );
+
+    assert_eq!(
+        parse_args(["-A", "x", "a"]),
+        Err(ConfigError::InvalidValue("after-context", "x".to_string()))
+    );
+}
+
+#[test]
+fn test_color_option() {
+    assert_eq!(search(&["a"]).color, ColorChoice::Never);
+    assert_eq!(search(&["--color=always", "a"]).color, ColorChoice::Always);
+    assert_eq!(search(&["--color", "auto", "a"]).color, ColorChoice::Auto);
+    assert_eq!(search(&["--color=tty", "a"]).color, ColorChoice::Auto);
+    assert_eq!(
+        parse_args(["--color=rojo", "a"]),
+        Err(ConfigError::InvalidValue("color", "rojo".to_string()))
+    );
+}
+
+#[test]
+fn test_word_and_line_options() {
+    let config = search(&["-wx", "a"]);
+    assert!(config.word_regexp && config.line_regexp);
+
+    let config = search(&["--word-regexp", "a"]);
+    assert!(config.word_regexp && !config.line_regexp);
+}
@@ -0,0 +1,46 @@
+use grep::glob::Glob;
+use std::path::Path;
+
+#[test]
+fn test_wildcards() {
+    assert!(Glob::new("*.rs").is_match("main.rs"));
+    assert!(Glob::new("*.rs").is_match(".rs"));
+    assert!(!Glob::new("*.rs").is_match("main.rs.bak"));
+    assert!(!Glob::new("*.rs").is_match("src/main.rs"));
+    assert!(Glob::new("file?.txt").is_match("file1.txt"));
+    assert!(!Glob::new("file?.txt").is_match("file.txt"));
+    assert!(Glob::new("a*b*c").is_match("aXXbYYbc"));
+}
+
+#[test]
+fn test_sets() {
+    let glob = Glob::new("[a-c]x[!0-9]");
+    assert!(glob.is_match("bxz"));
+    assert!(!glob.is_match("dxz"));
+    assert!(!glob.is_match("bx5"));
+    assert!(Glob::new("[^a]").is_match("b"));
+    assert!(Glob::new("[]a]").is_match("]"));
+    assert!(Glob::new("[a-]").is_match("-"));
+
+    // Un '[' sin cerrar es literal
+    assert!(Glob::new("a[b").is_match("a[b"));
+    assert!(Glob::new("\\*").is_match("*"));
+    assert!(!Glob::new("\\*").is_match("a"));
+}
+
+#[test]
+fn test_double_star() {
+    let glob = Glob::new("src/**/*.rs");
+    assert!(glob.is_match("src/main.rs"));
+    assert!(glob.is_match("src/a/b/main.rs"));
+    assert!(!glob.is_match("test/main.rs"));
+    assert!(Glob::new("**/x").is_match("x"));
+    assert!(Glob::new("a/**").is_match("a/b/c"));
+}
+
+#[test]
+fn test_matches_path() {
+    assert!(Glob::new("*.rs").matches_path(Path::new("src/main.rs")));
+    assert!(Glob::new("src/*.rs").matches_path(Path::new("./src/main.rs")));
+    assert!(!Glob::new("src/*.rs").matches_path(Path::new("other/main.rs")));
+}
@@ -0,0 +1,53 @@
+use grep::ignore::{IgnoreFile, IgnoreMatch, IgnoreRule};
+use std::path::Path;
+
+fn matched(file: &IgnoreFile, path: &str, is_dir: bool) -> IgnoreMatch {
+    file.matched(&Path::new("/repo").join(path), is_dir)
+}
+
+#[test]
+fn test_parse_rule() {
+    assert_eq!(IgnoreRule::parse(""), None);
+    assert_eq!(IgnoreRule::parse("# comentario"), None);
+
+    let rule = IgnoreRule::parse("!/build/  ").unwrap();
+    assert!(rule.negated && rule.dir_only && rule.anchored);
+    assert_eq!(rule.glob.pattern(), "build");
+
+    let rule = IgnoreRule::parse("\\#file").unwrap();
+    assert!(!rule.negated && !rule.anchored);
+    assert_eq!(rule.glob.pattern(), "#file");
+}
+
+#[test]
+fn test_unanchored_and_anchored() {
+    let file = IgnoreFile::parse("/repo", "*.log\n/todo.txt\ndoc/*.md\n");
+
+    assert_eq!(matched(&file, "a.log", false), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "src/deep/a.log", false), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "todo.txt", false), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "src/todo.txt", false), IgnoreMatch::None);
+    assert_eq!(matched(&file, "doc/a.md", false), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "doc/sub/a.md", false), IgnoreMatch::None);
+    assert_eq!(matched(&file, "src/doc/a.md", false), IgnoreMatch::None);
+}
+
+#[test]
+fn test_dir_only_and_negation() {
+    let file = IgnoreFile::parse("/repo", "target/\n*.txt\n!keep.txt\n");
+
+    assert_eq!(matched(&file, "target", true), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "sub/target", true), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "target", false), IgnoreMatch::None);
+    assert_eq!(matched(&file, "a.txt", false), IgnoreMatch::Ignore);
+    assert_eq!(matched(&file, "keep.txt", false), IgnoreMatch::Whitelist);
+    assert_eq!(matched(&file, "other/a.txt", false), IgnoreMatch::Ignore);
+}
+
+#[test]
+fn test_outside_directory() {
+    let file = IgnoreFile::parse("/repo/sub", "*.log\n");
+
+    assert_eq!(matched(&file, "a.log", false), IgnoreMatch::None);
+    assert_eq!(matched(&file, "sub/a.log", false), IgnoreMatch::Ignore);
+}
@@ -1,4 +1,11 @@
-use grep::regex::Regex;
+use grep::literal_matcher::LiteralMatcher;
+use grep::matcher::Matcher;
+use grep::regex::{self, Error, MatchBoundary, MatchSemantics, Regex, Span};
+use grep::regex_ast::{self, Ast, RepetitionOp};
+use grep::regex_class::RegexClass;
+use grep::regex_hir::Hir;
+use grep::regex_match::Match;
+use grep::regex_pattern::Pattern;
 
 #[test]
 fn test_period() {
@@ -10,18 +17,12 @@ fn test_period() {
     let value5 = "zyxabcdefghi";
     let value6 = "zyxabdefghi";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
-    );
-    assert_eq!(Regex::new(expression).unwrap().test(&value3).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value4).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value5).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value6).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value6).unwrap());
 }
 
 #[test]
@@ -34,12 +35,12 @@ fn test_period_and_repetition() {
     let value5 = "zyxabxxxxcdefghi";
     let value6 = "zyxabcdefghi";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value3).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value4).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value5).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value6).unwrap(), true);
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value6).unwrap());
 }
 
 #[test]
@@ -50,16 +51,10 @@ fn test_brackets() {
     let value3 = "ad";
     let value4 = "aed";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
 }
 
 #[test]
@@ -72,21 +67,12 @@ fn test_braces() {
     let value5 = "abcd";
     let value6 = "accd";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value3).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value5).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value6).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value6).unwrap());
 }
 
 #[test]
@@ -100,16 +86,13 @@ fn test_vertical_var_and_plus() {
     let value6 = "abdeeef";
     let value7 = "abcdf";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value3).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
-    assert_eq!(Regex::new(expression).unwrap().test(&value5).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value6).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value7).unwrap(), true);
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value6).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value7).unwrap());
 }
 
 #[test]
@@ -122,15 +105,12 @@ fn test_text_and_brackets() {
     let value5 = "la u es una vocal";
     let value6 = "la b es una vocal";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value3).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value4).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value5).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value6).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value6).unwrap());
 }
 
 #[test]
@@ -143,27 +123,12 @@ fn test_text_and_brackets_denied() {
     let value5 = "la u es una vocal";
     let value6 = "la b es una vocal";
 
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value1).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value5).unwrap(),
-        false
-    );
-    assert_eq!(Regex::new(expression).unwrap().test(&value6).unwrap(), true);
+    assert!(!Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value5).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value6).unwrap());
 }
 
 #[test]
@@ -175,20 +140,11 @@ fn test_alpha_and_plus() {
     let value4 = "hola 1";
     let value5 = "hola 11";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value5).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value5).unwrap());
 }
 
 #[test]
@@ -197,11 +153,8 @@ fn test_digit_and_text() {
     let value1 = "45 es un numero";
     let value2 = "a es un numero";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
 }
 
 #[test]
@@ -211,12 +164,9 @@ fn test_alnum_and_text() {
     let value2 = "el caracter 4 no es un simbolo";
     let value3 = "el caracter ? no es un simbolo";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(Regex::new(expression).unwrap().test(&value2).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
 }
 
 #[test]
@@ -226,15 +176,9 @@ fn test_space_and_text() {
     let value2 = "holamundo";
     let value3 = "holaamundo";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
 }
 
 #[test]
@@ -245,19 +189,10 @@ fn test_upper_and_text() {
     let value3 = "pascalPase";
     let value4 = "pascalpase";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
-    );
-    assert_eq!(
-        Regex::new(expression).unwrap().test(&value4).unwrap(),
-        false
-    );
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value4).unwrap());
 }
 
 #[test]
@@ -267,13 +202,504 @@ fn test_anchoring() {
     let value2 = "es el fin no?";
     let value3 = "es el finde";
 
-    assert_eq!(Regex::new(expression).unwrap().test(&value1).unwrap(), true);
+    assert!(Regex::new(expression).unwrap().test(value1).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value2).unwrap());
+    assert!(!Regex::new(expression).unwrap().test(value3).unwrap());
+}
+
+#[test]
+fn test_find_leftmost_first() {
+    let regex = Regex::new("ab|abcd").unwrap();
+    let value = b"xxabcd";
+
+    assert_eq!(regex.find(value), Some(Match::new(2, 4)));
+}
+
+#[test]
+fn test_find_leftmost_longest() {
+    let regex = Regex::new("ab|abcd")
+        .unwrap()
+        .with_semantics(MatchSemantics::LeftmostLongest);
+    let value = b"xxabcd";
+
+    assert_eq!(regex.find(value), Some(Match::new(2, 6)));
+}
+
+#[test]
+fn test_find_leftmost_longest_prefers_leftmost() {
+    let regex = Regex::new("bcde|ab")
+        .unwrap()
+        .with_semantics(MatchSemantics::LeftmostLongest);
+    let value = b"abcde";
+
+    assert_eq!(regex.find(value), Some(Match::new(0, 2)));
+}
+
+#[test]
+fn test_find_iter() {
+    let regex = Regex::new("[[:digit:]]+").unwrap();
+    let value = b"id 12, id 345 y 6";
+
+    let matches: Vec<Match> = regex.find_iter(value).collect();
+
     assert_eq!(
-        Regex::new(expression).unwrap().test(&value2).unwrap(),
-        false
+        matches,
+        vec![Match::new(3, 5), Match::new(10, 13), Match::new(16, 17)]
     );
+}
+
+#[test]
+fn test_find_iter_empty_matches() {
+    let regex = Regex::new("a*").unwrap();
+    let value = b"baab";
+
+    let matches: Vec<Match> = regex.find_iter(value).collect();
+
     assert_eq!(
-        Regex::new(expression).unwrap().test(&value3).unwrap(),
-        false
+        matches,
+        vec![Match::new(0, 0), Match::new(1, 3), Match::new(4, 4)]
     );
 }
+
+#[test]
+fn test_captures_semantics() {
+    let value = b"abcd";
+
+    let first = Regex::new("(a|ab)(c|bcd)").unwrap();
+    let captures = first.captures(value).unwrap();
+    assert_eq!(captures.get(0), Some(Match::new(0, 4)));
+    assert_eq!(captures.get(1), Some(Match::new(0, 1)));
+    assert_eq!(captures.get(2), Some(Match::new(1, 4)));
+
+    let longest = Regex::new("(ab|a)(c|bcd)?")
+        .unwrap()
+        .with_semantics(MatchSemantics::LeftmostLongest);
+    let captures = longest.captures(value).unwrap();
+    assert_eq!(captures.get(0), Some(Match::new(0, 4)));
+    assert_eq!(captures.len(), 3);
+}
+
+#[test]
+fn test_anchors_with_find_at() {
+    let regex = Regex::new("^a").unwrap();
+    let value = b"aa";
+
+    assert_eq!(regex.find_at(value, 0), Some(Match::new(0, 1)));
+    assert_eq!(regex.find_at(value, 1), None);
+}
+
+#[test]
+fn test_literal_metacharacters() {
+    let matcher = LiteralMatcher::new(&["a.b[0]"]);
+
+    assert_eq!(matcher.find(b"x = a.b[0];"), Some(Match::new(4, 10)));
+    assert!(!matcher.is_match(b"axb0"));
+}
+
+#[test]
+fn test_literal_multiple_patterns() {
+    let matcher = LiteralMatcher::new(&["he", "she", "his", "hers"]);
+    let value = b"ushers";
+
+    let matches: Vec<Match> = matcher.find_iter(value).collect();
+
+    assert_eq!(matches, vec![Match::new(1, 4)]);
+}
+
+#[test]
+fn test_literal_semantics() {
+    let value = b"xxabcd";
+
+    let first = LiteralMatcher::new(&["ab", "abcd"]);
+    assert_eq!(first.find(value), Some(Match::new(2, 4)));
+
+    let longest =
+        LiteralMatcher::new(&["ab", "abcd"]).with_semantics(MatchSemantics::LeftmostLongest);
+    assert_eq!(longest.find(value), Some(Match::new(2, 6)));
+
+    let leftmost =
+        LiteralMatcher::new(&["bcd", "abc"]).with_semantics(MatchSemantics::LeftmostLongest);
+    assert_eq!(leftmost.find(value), Some(Match::new(2, 5)));
+}
+
+#[test]
+fn test_literal_empty_pattern() {
+    let matcher = LiteralMatcher::new(&["", "b"]);
+
+    assert!(matcher.is_match(b"xyz"));
+    assert_eq!(matcher.find_at(b"ab", 1), Some(Match::new(1, 1)));
+}
+
+#[test]
+fn test_error_spans() {
+    let unclosed = Regex::new("a[bc").unwrap_err();
+    assert!(matches!(unclosed, Error::UnclosedBracket { .. }));
+    assert_eq!(unclosed.span(), Span::new(1, 2));
+
+    let paren = Regex::new("(ab").unwrap_err();
+    assert!(matches!(paren, Error::UnbalancedParen { .. }));
+    assert_eq!(paren.span(), Span::new(0, 1));
+
+    let extra_paren = Regex::new("ab)c").unwrap_err();
+    assert!(matches!(extra_paren, Error::UnbalancedParen { .. }));
+    assert_eq!(extra_paren.span(), Span::new(2, 3));
+
+    let class = Regex::new("[[:foo:]]").unwrap_err();
+    assert!(matches!(class, Error::InvalidClassName { .. }));
+    assert_eq!(class.span(), Span::new(1, 8));
+
+    let repetition = Regex::new("ab{3,1}").unwrap_err();
+    assert!(matches!(repetition, Error::BadRepetition { .. }));
+    assert_eq!(repetition.span(), Span::new(2, 7));
+
+    let nothing = Regex::new("*a").unwrap_err();
+    assert!(matches!(nothing, Error::NothingToRepeat { .. }));
+    assert_eq!(nothing.span(), Span::new(0, 1));
+
+    let backslash = Regex::new("ab\\").unwrap_err();
+    assert!(matches!(backslash, Error::TrailingBackslash { .. }));
+
+    let size = Regex::new("a{1000}{1000}").unwrap_err();
+    assert!(matches!(size, Error::SizeLimit { .. }));
+
+    // Una repeticion enorme falla enseguida, sin compilar todas las copias
+    for pattern in ["a{100000000000}", "a{1,100000000000}"] {
+        let size = Regex::new(pattern).unwrap_err();
+        assert!(matches!(size, Error::SizeLimit { .. }));
+    }
+    // Las copias de un grupo vacio no agregan nada, asi que no hace falta seguir
+    assert!(Regex::new("(?:){100000000000}a").unwrap().is_match(b"a"));
+    let huge = Pattern::literal("a").repeat(100_000_000_000..).build();
+    assert!(matches!(huge.unwrap_err(), Error::SizeLimit { .. }));
+}
+
+#[test]
+fn test_error_display() {
+    let error = Regex::new("hola (mundo").unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "regex parse error:\n    hola (mundo\n         ^\nerror: unbalanced parenthesis"
+    );
+}
+
+#[test]
+fn test_bracket_ranges_and_classes() {
+    let regex = Regex::new("^[a-c0-9_[:upper:]-]+$").unwrap();
+
+    assert!(regex.is_match(b"abc_09-XYZ"));
+    assert!(!regex.is_match(b"abd"));
+
+    let negated = Regex::new("[^a-z]").unwrap();
+    assert!(negated.is_match(b"abC"));
+    assert!(!negated.is_match(b"abc"));
+
+    assert!(matches!(
+        Regex::new("[z-a]").unwrap_err(),
+        Error::InvalidRange { .. }
+    ));
+}
+
+#[test]
+fn test_ast_round_trip() {
+    let patterns = [
+        "ab.*cd",
+        "la [^aeiou] es una vocal",
+        "hola [[:alpha:]]+",
+        "(a|b)(?:c{2}|d{1,}|e{,3})?\\.$",
+        "[]a-]x",
+        "^$",
+        "a|",
+        "(?P<clave>[a-z]+)=(.*)",
+    ];
+
+    for pattern in patterns {
+        let ast = regex_ast::parse(pattern).unwrap();
+        assert_eq!(ast.to_string(), pattern);
+    }
+}
+
+#[test]
+fn test_ast_spans() {
+    let ast = regex_ast::parse("ab+").unwrap();
+
+    match ast {
+        Ast::Concat { span, asts } => {
+            assert_eq!(span, Span::new(0, 3));
+            assert_eq!(asts[1].span(), Span::new(1, 3));
+            assert!(matches!(
+                asts[1],
+                Ast::Repetition {
+                    op: RepetitionOp::Plus,
+                    ..
+                }
+            ));
+        }
+        _ => panic!("expected a concatenation"),
+    }
+}
+
+#[test]
+fn test_hir_normalization() {
+    let hir = |pattern| Hir::from_ast(&regex_ast::parse(pattern).unwrap());
+
+    assert_eq!(hir("[cba]"), hir("[a-c]"));
+    assert_eq!(hir("[[:digit:][:digit:]]"), hir("[[:digit:]]"));
+    assert_eq!(hir("a{1,}"), hir("a+"));
+    assert_eq!(hir("a{0,1}"), hir("a?"));
+    assert_eq!(hir("a{1}"), hir("a"));
+    assert_eq!(hir("[x]"), hir("x"));
+
+    assert_eq!(hir("[dcba-]").to_string(), "[a-d-]");
+    assert_eq!(hir("a{1,}b{0,}c{0,1}d{2,2}").to_string(), "a+b*c?d{2}");
+    assert_eq!(hir("[-^]").to_string(), "[-^]");
+    assert_eq!(hir("[]^]").to_string(), "[]^]");
+}
+
+#[test]
+fn test_hir_round_trip() {
+    let patterns = [
+        "ab{2,4}cd",
+        "[^-a-z]x",
+        "(ab|c)*d",
+        "a\\.b\\[0\\]",
+        "[][:punct:]]",
+    ];
+
+    for pattern in patterns {
+        let hir = Hir::from_ast(&regex_ast::parse(pattern).unwrap());
+        let printed = hir.to_string();
+        let reparsed = Hir::from_ast(&regex_ast::parse(&printed).unwrap());
+        assert_eq!(reparsed, hir, "{} -> {}", pattern, printed);
+    }
+}
+
+#[test]
+fn test_hir_display_always_parses() {
+    let patterns = [
+        Pattern::line_start().zero_or_more(),
+        Pattern::line_end().optional().then(Pattern::literal("a")),
+        Pattern::one_of(""),
+        Pattern::none_of("").one_or_more(),
+    ];
+
+    for pattern in patterns {
+        let built = pattern.build().unwrap();
+        let printed = pattern.to_string();
+        let parsed = Regex::new(&printed).unwrap();
+        let reprinted = Hir::from_ast(&regex_ast::parse(&printed).unwrap()).to_string();
+        assert_eq!(reprinted, printed);
+        for haystack in ["", "a", "\n", "ba^"] {
+            let haystack = haystack.as_bytes();
+            assert_eq!(parsed.find(haystack), built.find(haystack), "{}", printed);
+        }
+    }
+
+    assert_eq!(Pattern::line_start().zero_or_more().to_string(), "(?:^)*");
+    assert_eq!(Pattern::none_of("").to_string(), ".");
+
+    // Sin patrones no matchea nada
+    let regex = Regex::new_many::<&str>(&[]).unwrap();
+    assert!(!regex.is_match(b"a"));
+    assert!(!regex.is_match(b""));
+}
+
+#[test]
+fn test_escape() {
+    let escaped = regex::escape("a.b[0]*(x|y)$");
+
+    assert_eq!(escaped, "a\\.b\\[0\\]\\*\\(x\\|y\\)\\$");
+
+    let regex = Regex::new(&escaped).unwrap();
+    assert!(regex.is_match(b"value: a.b[0]*(x|y)$"));
+    assert!(!regex.is_match(b"axb0"));
+}
+
+#[test]
+fn test_pattern_builder_same_steps() {
+    let built = Pattern::literal("a.b")
+        .then(Pattern::class(RegexClass::Digit).repeat(1..=3))
+        .or(Pattern::one_of("xy").one_or_more())
+        .build()
+        .unwrap();
+    let parsed = Regex::new("a\\.b[[:digit:]]{1,3}|[xy]+").unwrap();
+
+    assert_eq!(built.steps(), parsed.steps());
+    assert!(built.is_match(b"a.b12"));
+    assert!(!built.is_match(b"azb12"));
+}
+
+#[test]
+fn test_pattern_builder_groups() {
+    let pattern = Pattern::line_start()
+        .then(Pattern::range('a', 'z').one_or_more().group())
+        .then(Pattern::literal("="))
+        .then(Pattern::any().zero_or_more().group())
+        .then(Pattern::line_end());
+
+    assert_eq!(pattern.to_string(), "^([a-z]+)=(.*)$");
+
+    let regex = pattern.build().unwrap();
+    let captures = regex.captures(b"key=value").unwrap();
+    assert_eq!(captures.get(1), Some(Match::new(0, 3)));
+    assert_eq!(captures.get(2), Some(Match::new(4, 9)));
+}
+
+#[test]
+fn test_pattern_builder_bad_repetition() {
+    let (min, max) = (3, 1);
+    let error = Pattern::literal("a").repeat(min..=max).build().unwrap_err();
+
+    assert!(matches!(error, Error::BadRepetition { .. }));
+}
+
+#[test]
+fn test_word_boundary() {
+    let regex = Regex::new("id").unwrap().with_boundary(MatchBoundary::Word);
+
+    assert_eq!(regex.find(b"valid id"), Some(Match::new(6, 8)));
+    assert_eq!(regex.find(b"(id)"), Some(Match::new(1, 3)));
+    assert!(!regex.is_match("valid id_x idé".as_bytes()));
+
+    // Si el match mas largo no es una palabra, se prueba uno mas corto en el mismo lugar
+    let regex = Regex::new("ab|abc")
+        .unwrap()
+        .with_semantics(MatchSemantics::LeftmostLongest)
+        .with_boundary(MatchBoundary::Word);
+    assert_eq!(regex.find(b"ab-abcd abc"), Some(Match::new(0, 2)));
+    assert_eq!(regex.find_at(b"ab-abcd abc", 2), Some(Match::new(8, 11)));
+
+    let regex = Regex::new("x*").unwrap().with_boundary(MatchBoundary::Word);
+    assert_eq!(regex.find(b"ab cd"), None);
+    assert_eq!(regex.find(b"ab  cd"), Some(Match::new(3, 3)));
+}
+
+#[test]
+fn test_ignore_case() {
+    let regex = Regex::new("straße").unwrap().with_ignore_case(true);
+    assert!(regex.ignore_case());
+    assert_eq!(regex.find("STRAßE".as_bytes()), Some(Match::new(0, 7)));
+    assert!(regex.is_match("Straße".as_bytes()));
+
+    let regex = Regex::new("[a-c]x").unwrap().with_ignore_case(true);
+    assert_eq!(regex.find(b"zBX"), Some(Match::new(1, 3)));
+
+    // Una clase negada tampoco acepta la otra forma de la letra
+    let regex = Regex::new("[^a]").unwrap().with_ignore_case(true);
+    assert_eq!(regex.find(b"Ab"), Some(Match::new(1, 2)));
+
+    let regex = Regex::new("[[:upper:]]+").unwrap().with_ignore_case(true);
+    assert_eq!(regex.find(b"1abC"), Some(Match::new(1, 4)));
+
+    // Sin -i, o al desactivarlo, se vuelve a distinguir
+    let regex = regex.with_ignore_case(false);
+    assert!(!regex.ignore_case());
+    assert_eq!(regex.find(b"1abC"), Some(Match::new(3, 4)));
+}
+
+#[test]
+fn test_line_boundary() {
+    let regex = Regex::new("ab|abc")
+        .unwrap()
+        .with_boundary(MatchBoundary::Line);
+
+    assert_eq!(regex.find(b"abc"), Some(Match::new(0, 3)));
+    assert_eq!(regex.find(b"xab\nab\n"), Some(Match::new(4, 6)));
+    assert!(!regex.is_match(b"abcd\n ab"));
+    assert_eq!(regex.boundary(), MatchBoundary::Line);
+}
+
+#[test]
+fn test_line_terminator() {
+    let regex = Regex::new("a.*").unwrap();
+    assert_eq!(regex.find(b"xa1\nb2"), Some(Match::new(1, 6)));
+    assert_eq!(regex.line_terminator(), None);
+
+    // Ni '.' ni las clases negadas consumen el terminador
+    let regex = regex.with_line_terminator(Some(b'\n'));
+    assert_eq!(regex.find(b"xa1\nb2"), Some(Match::new(1, 3)));
+    let regex = Regex::new("1[^x]+")
+        .unwrap()
+        .with_line_terminator(Some(b'\n'));
+    assert_eq!(regex.find(b"1\nb 1ab\n"), Some(Match::new(4, 7)));
+    let regex = Regex::new("a[[:space:]]b")
+        .unwrap()
+        .with_line_terminator(Some(b'\n'));
+    assert!(!regex.is_match(b"a\nb"));
+}
+
+#[test]
+fn test_literal_boundaries() {
+    let words = LiteralMatcher::new(&["id", "valid"]).with_boundary(MatchBoundary::Word);
+    let matches: Vec<Match> = words.find_iter(b"valid ids id").collect();
+    assert_eq!(matches, vec![Match::new(0, 5), Match::new(10, 12)]);
+
+    let lines = LiteralMatcher::new(&["id"]).with_boundary(MatchBoundary::Line);
+    assert_eq!(lines.find(b"idid\nid\n"), Some(Match::new(5, 7)));
+
+    // El patron vacio solo matchea donde se cumplen los bordes
+    let empty = LiteralMatcher::new(&[""]).with_boundary(MatchBoundary::Line);
+    assert_eq!(empty.find(b"ab\n\ncd"), Some(Match::new(3, 3)));
+    assert_eq!(empty.find(b"ab"), None);
+}
+
+#[test]
+fn test_many_patterns() {
+    let regex = Regex::new_many(&["a(b)", "c|d", "^e"]).unwrap();
+    let matches: Vec<Match> = regex.find_iter(b"xab d ec\ne").collect();
+    assert_eq!(
+        matches,
+        vec![
+            Match::new(1, 3),
+            Match::new(4, 5),
+            Match::new(7, 8),
+            Match::new(9, 10)
+        ]
+    );
+    assert_eq!(regex.captures_len(), 2);
+
+    // Un patron vacio matchea en todos lados, y sin patrones no matchea nada
+    assert!(Regex::new_many(&["x", ""]).unwrap().is_match(b"abc"));
+    assert!(!Regex::new_many::<&str>(&[]).unwrap().is_match(b"abc"));
+}
+
+#[test]
+fn test_many_patterns_error() {
+    let err = Regex::new_many(&["a", "b(", "[c"]).unwrap_err();
+
+    assert_eq!(err.index, Some(1));
+    assert_eq!(err.error.pattern(), "b(");
+    assert_eq!(err.error.span(), Span::new(1, 2));
+}
+
+#[test]
+fn test_named_groups() {
+    let regex = Regex::new("(?P<clave>[a-z]+)=(?<valor>[0-9]*)").unwrap();
+    assert_eq!(regex.captures_len(), 3);
+    assert_eq!(regex.capture_index("clave"), Some(1));
+    assert_eq!(regex.capture_index("valor"), Some(2));
+    assert_eq!(regex.capture_index("otro"), None);
+
+    let captures = regex.captures(b"x: id=42").unwrap();
+    assert_eq!(captures.get(1), Some(Match::new(3, 5)));
+    assert_eq!(captures.get(2), Some(Match::new(6, 8)));
+
+    // Con varios patrones, cada uno numera sus grupos
+    let regex = Regex::new_many(&["(?P<a>x)", "y(?P<b>z)"]).unwrap();
+    assert_eq!(regex.capture_index("a"), Some(1));
+    assert_eq!(regex.capture_index("b"), Some(1));
+
+    let invalid = Regex::new("(?P<1a>x)").unwrap_err();
+    assert!(matches!(invalid, Error::InvalidGroupName { .. }));
+    assert_eq!(invalid.span(), Span::new(4, 6));
+
+    let empty = Regex::new("(?<>x)").unwrap_err();
+    assert!(matches!(empty, Error::InvalidGroupName { .. }));
+
+    let duplicate = Regex::new("(?P<a>x)(?P<a>y)").unwrap_err();
+    assert!(matches!(duplicate, Error::DuplicateGroupName { .. }));
+    assert_eq!(duplicate.description(), "duplicate capture group name 'a'");
+
+    let unclosed = Regex::new("(?P<a").unwrap_err();
+    assert!(matches!(unclosed, Error::UnbalancedParen { .. }));
+}
@@ -0,0 +1,122 @@
+use grep::config::Config;
+use grep::json::{base64, write_data, write_string, JsonPrinter, Stats};
+use grep::regex::Regex;
+use grep::searcher::Searcher;
+
+fn string(text: &str) -> String {
+    let mut out = vec![];
+    write_string(&mut out, text);
+    String::from_utf8(out).unwrap()
+}
+
+fn data(bytes: &[u8]) -> String {
+    let mut out = vec![];
+    write_data(&mut out, bytes);
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_write_string() {
+    assert_eq!(string("uno"), "\"uno\"");
+    assert_eq!(string("a\"b\\c"), "\"a\\\"b\\\\c\"");
+    assert_eq!(string("\n\r\t\x01\x1f"), "\"\\n\\r\\t\\u0001\\u001f\"");
+    // Lo que no es de control se escribe tal cual, aunque no sea ASCII
+    assert_eq!(string("año €"), "\"año €\"");
+}
+
+#[test]
+fn test_base64() {
+    assert_eq!(base64(b""), "");
+    assert_eq!(base64(b"f"), "Zg==");
+    assert_eq!(base64(b"fo"), "Zm8=");
+    assert_eq!(base64(b"foo"), "Zm9v");
+    assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(base64(&[0xFF, 0xFE, 0x00]), "//4A");
+}
+
+#[test]
+fn test_write_data() {
+    assert_eq!(data(b"uno\n"), "{\"text\":\"uno\\n\"}");
+    assert_eq!(data(b"ab\xFFcd\n"), "{\"bytes\":\"YWL/Y2QK\"}");
+}
+
+#[test]
+fn test_json_printer() {
+    let config = Config::default();
+    let regex = Regex::new("o").unwrap();
+    let mut out = vec![];
+    let mut printer = JsonPrinter::new(&config, &regex, "a.txt", &mut out);
+    Searcher::new()
+        .line_number(true)
+        .after_context(1)
+        .search_slice(&regex, b"uno dos\ntres\ncuatro\n", &mut printer)
+        .unwrap();
+    printer.finish().unwrap();
+    let stats = *printer.stats();
+
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(
+        lines[0],
+        r#"{"type":"begin","data":{"path":{"text":"a.txt"}}}"#
+    );
+    assert_eq!(
+        lines[1],
+        r#"{"type":"match","data":{"path":{"text":"a.txt"},"lines":{"text":"uno dos\n"},"line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"o"},"start":2,"end":3},{"match":{"text":"o"},"start":5,"end":6}]}}"#
+    );
+    assert_eq!(
+        lines[2],
+        r#"{"type":"context","data":{"path":{"text":"a.txt"},"lines":{"text":"tres\n"},"line_number":2,"absolute_offset":8,"submatches":[]}}"#
+    );
+    assert!(lines[3].contains(r#""absolute_offset":13,"#));
+    assert!(lines[4].starts_with(r#"{"type":"end","data":{"path":{"text":"a.txt"},"stats":"#));
+    // Las estadisticas del mensaje end cuentan los bytes escritos antes de el
+    let printed = lines[..4].iter().map(|line| line.len() + 1).sum::<usize>();
+    let end = format!(
+        r#""searches":1,"searches_with_match":1,"bytes_printed":{},"matched_lines":2,"matches":3}}}}}}"#,
+        printed
+    );
+    assert!(lines[4].ends_with(&end));
+
+    // El mensaje end tambien se cuenta en el total
+    assert_eq!(stats.bytes_printed as usize, out.len());
+    assert_eq!((stats.matched_lines, stats.matches), (2, 3));
+}
+
+#[test]
+fn test_json_printer_without_results() {
+    let config = Config::default();
+    let regex = Regex::new("z").unwrap();
+    let mut out = vec![];
+    let mut printer = JsonPrinter::new(&config, &regex, "a.txt", &mut out);
+    Searcher::new()
+        .search_slice(&regex, b"uno\n", &mut printer)
+        .unwrap();
+    printer.finish().unwrap();
+
+    // Sin resultados no se escribe ni begin ni end, pero la busqueda se cuenta
+    let stats = *printer.stats();
+    assert_eq!((stats.searches, stats.searches_with_match), (1, 0));
+    assert!(out.is_empty());
+}
+
+#[test]
+fn test_stats_add() {
+    let mut total = Stats::default();
+    let file = Stats {
+        searches: 1,
+        searches_with_match: 1,
+        bytes_printed: 10,
+        matched_lines: 2,
+        matches: 3,
+        ..Stats::default()
+    };
+    total.add(&file);
+    total.add(&file);
+    assert_eq!(
+        (total.searches, total.matched_lines, total.matches),
+        (2, 4, 6)
+    );
+    assert_eq!(total.bytes_printed, 20);
+}
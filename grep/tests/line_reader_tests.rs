@@ -0,0 +1,83 @@
+use grep::line_reader::LineReader;
+use std::io::{self, Read};
+
+/// Fuente que entrega de a pocos bytes por lectura, como un pipe
+struct Trickle<'a> {
+    data: &'a [u8],
+    step: usize,
+}
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.step.min(buf.len()).min(self.data.len());
+        buf[..len].copy_from_slice(&self.data[..len]);
+        self.data = &self.data[len..];
+        Ok(len)
+    }
+}
+
+fn lines<R: Read>(mut reader: LineReader<R>) -> Vec<Vec<u8>> {
+    let mut lines = vec![];
+    while let Some(line) = reader.next_line().unwrap() {
+        lines.push(line.to_vec());
+    }
+    lines
+}
+
+#[test]
+fn test_next_line() {
+    let reader = LineReader::new(&b"uno\ndos\n\ntres"[..]);
+
+    assert_eq!(
+        lines(reader),
+        vec![
+            b"uno\n".to_vec(),
+            b"dos\n".to_vec(),
+            b"\n".to_vec(),
+            b"tres".to_vec()
+        ]
+    );
+    assert!(lines(LineReader::new(&b""[..])).is_empty());
+}
+
+#[test]
+fn test_small_reads_and_buffer() {
+    let data = b"primera linea\nsegunda\ntercera linea mas larga\n";
+    for step in 1..6 {
+        let source = Trickle { data, step };
+        let reader = LineReader::with_capacity(source, 4);
+        assert_eq!(lines(reader).concat(), data.to_vec());
+    }
+}
+
+#[test]
+fn test_line_longer_than_buffer() {
+    let long = "x".repeat(1000);
+    let data = format!("a\n{}\nb\n", long);
+    let mut reader = LineReader::with_capacity(data.as_bytes(), 16);
+
+    assert_eq!(reader.next_line().unwrap(), Some(&b"a\n"[..]));
+    assert_eq!(reader.next_line().unwrap().map(|l| l.len()), Some(1001));
+    assert!(reader.capacity() >= 1001);
+    assert_eq!(reader.offset(), 1003);
+    assert_eq!(reader.next_line().unwrap(), Some(&b"b\n"[..]));
+    assert_eq!(reader.next_line().unwrap(), None);
+}
+
+#[test]
+fn test_fill_returns_complete_lines() {
+    let source = Trickle {
+        data: b"ab\ncd\nef",
+        step: 4,
+    };
+    let mut reader = LineReader::with_capacity(source, 64);
+
+    assert_eq!(reader.fill().unwrap(), b"ab\n");
+    reader.consume(3);
+    assert_eq!(reader.fill().unwrap(), b"cd\n");
+    reader.consume(3);
+    assert_eq!(reader.fill().unwrap(), b"ef");
+    reader.consume(2);
+    assert_eq!(reader.fill().unwrap(), b"");
+    assert_eq!(reader.offset(), 8);
+}
@@ -0,0 +1,67 @@
+use grep::mmap::{Mmap, MmapChoice, MMAP_MAX_FILES, MMAP_MIN_SIZE};
+use grep::regex::Regex;
+use grep::searcher::{Searcher, Sink, SinkMatch};
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("grep-mmap-{}-{}", name, std::process::id()));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+struct Lines(Vec<Vec<u8>>);
+
+impl Sink for Lines {
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool> {
+        self.0.push(found.bytes.to_vec());
+        Ok(true)
+    }
+}
+
+#[test]
+fn test_map_contents() {
+    let path = temp_file("contents", b"hola\nmundo\n");
+    let map = Mmap::map(&File::open(&path).unwrap()).unwrap();
+    assert_eq!(&map[..], b"hola\nmundo\n");
+
+    let path = temp_file("empty", b"");
+    let map = Mmap::map(&File::open(&path).unwrap()).unwrap();
+    assert!(map.is_empty());
+}
+
+#[test]
+fn test_should_map() {
+    let small = temp_file("small", b"abc");
+    let small = File::open(small).unwrap().metadata().unwrap();
+    let large = temp_file("large", &vec![b'a'; MMAP_MIN_SIZE as usize]);
+    let large = File::open(large).unwrap().metadata().unwrap();
+    let dir = fs::metadata(std::env::temp_dir()).unwrap();
+
+    assert!(!MmapChoice::Auto.should_map(&small, 1));
+    assert!(MmapChoice::Auto.should_map(&large, 1));
+    assert!(!MmapChoice::Auto.should_map(&large, MMAP_MAX_FILES + 1));
+    assert!(MmapChoice::Always.should_map(&small, 1000));
+    assert!(!MmapChoice::Always.should_map(&dir, 1));
+    assert!(!MmapChoice::Never.should_map(&large, 1));
+}
+
+#[test]
+fn test_search_file_mapped_or_read() {
+    let path = temp_file("search", b"uno\ndos\ntres\ncuatro");
+    let regex = Regex::new("o$").unwrap();
+
+    let mut results = vec![];
+    for memory_map in [false, true] {
+        let mut lines = Lines(vec![]);
+        Searcher::new()
+            .memory_map(memory_map)
+            .search_file(&regex, &File::open(&path).unwrap(), &mut lines)
+            .unwrap();
+        results.push(lines.0);
+    }
+
+    assert_eq!(results[0], vec![b"uno\n".to_vec(), b"cuatro".to_vec()]);
+    assert_eq!(results[0], results[1]);
+}
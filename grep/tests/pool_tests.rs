@@ -0,0 +1,125 @@
+use grep::output::SharedOutput;
+use grep::pool::WorkStealingPool;
+use std::cell::RefCell;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn test_pool_runs_every_job_once() {
+    let done = Mutex::new(vec![]);
+    let jobs: Vec<usize> = (0..100).map(|i| i * 10).collect();
+
+    WorkStealingPool::new(4).run(jobs, |index, value| {
+        // Los primeros trabajos tardan mas, asi los otros hilos tienen que robar
+        if index < 5 {
+            thread::sleep(Duration::from_millis(20));
+        }
+        done.lock().unwrap().push((index, value));
+    });
+
+    let mut done = done.into_inner().unwrap();
+    done.sort();
+    assert_eq!(done, (0..100).map(|i| (i, i * 10)).collect::<Vec<_>>());
+}
+
+#[test]
+fn test_pool_threads() {
+    assert_eq!(WorkStealingPool::new(0).threads(), 1);
+    assert!(WorkStealingPool::default().threads() >= 1);
+
+    // Sin trabajos no hace nada
+    WorkStealingPool::new(3).run(Vec::<u8>::new(), |_, _| panic!("no jobs"));
+}
+
+#[test]
+fn test_shared_output_ordered() {
+    let output = SharedOutput::new(vec![], true);
+    output.write(2, b"c".to_vec()).unwrap();
+    output.write(0, b"a".to_vec()).unwrap();
+    output.write(3, b"d".to_vec()).unwrap();
+    output.write(1, b"b".to_vec()).unwrap();
+
+    assert_eq!(output.into_inner(), b"abcd");
+}
+
+#[test]
+fn test_shared_output_unordered() {
+    let output = SharedOutput::new(vec![], false);
+    output.write(2, b"c".to_vec()).unwrap();
+    output.write(0, b"a".to_vec()).unwrap();
+
+    assert_eq!(output.into_inner(), b"ca");
+}
+
+/// Destino que se puede revisar mientras la salida lo sigue usando
+#[derive(Clone, Default)]
+struct Shared(Rc<RefCell<Vec<u8>>>);
+
+impl Shared {
+    fn text(&self) -> String {
+        String::from_utf8(self.0.borrow().clone()).unwrap()
+    }
+}
+
+impl Write for Shared {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().write(bytes)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_shared_output_streams_lines() {
+    // Sin buffer cada linea se escribe apenas se completa, antes de terminar el archivo
+    let written = Shared::default();
+    let output = SharedOutput::new(written.clone(), true).chunk_size(0);
+    let mut file = output.file(0);
+    file.write_all(b"uno\ndo").unwrap();
+    assert_eq!(written.text(), "uno\n");
+    file.write_all(b"s\n").unwrap();
+    assert_eq!(written.text(), "uno\ndos\n");
+
+    // Un archivo que no esta en su turno espera al anterior
+    let mut next = output.file(1);
+    next.write_all(b"tres\n").unwrap();
+    assert_eq!(written.text(), "uno\ndos\n");
+    file.finish().unwrap();
+    assert_eq!(written.text(), "uno\ndos\n");
+    next.write_all(b"cuatro\n").unwrap();
+    assert_eq!(written.text(), "uno\ndos\ntres\ncuatro\n");
+    next.finish().unwrap();
+}
+
+#[test]
+fn test_shared_output_chunks() {
+    let written = Shared::default();
+    let output = SharedOutput::new(written.clone(), false)
+        .chunk_size(8)
+        .separator(Some(b"--\n".to_vec()));
+
+    // El primer archivo que junta una parte se queda con el destino hasta terminar
+    let mut first = output.file(0);
+    let mut second = output.file(1);
+    let mut third = output.file(2);
+    first.write_all(b"uno\n").unwrap();
+    second.write_all(b"dos\ntres").unwrap();
+    assert_eq!(written.text(), "dos\n");
+    first.write_all(b"cinco\n").unwrap();
+    assert_eq!(written.text(), "dos\n");
+    third.write_all(b"seis\n").unwrap();
+    third.finish().unwrap();
+    assert_eq!(written.text(), "dos\n");
+
+    // Al terminar se escriben los que ya terminaron, y el resto sigue cuando le toque
+    second.write_all(b"\n").unwrap();
+    second.finish().unwrap();
+    assert_eq!(written.text(), "dos\ntres\n--\nseis\n");
+    first.finish().unwrap();
+    assert_eq!(written.text(), "dos\ntres\n--\nseis\n--\nuno\ncinco\n");
+}
@@ -0,0 +1,328 @@
+use grep::literal_matcher::LiteralMatcher;
+use grep::regex::Regex;
+use grep::searcher::{ContextKind, Searcher, Sink, SinkContext, SinkMatch};
+use std::io;
+
+/// Guarda cada resultado como (posicion, texto), y aparte los numeros de linea
+#[derive(Default)]
+struct Collect {
+    found: Vec<(u64, String)>,
+    line_numbers: Vec<Option<u64>>,
+    limit: Option<usize>,
+}
+
+impl Sink for Collect {
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool> {
+        let text = String::from_utf8_lossy(found.bytes).to_string();
+        self.found.push((found.absolute_offset, text));
+        self.line_numbers.push(found.line_number);
+        Ok(self.limit != Some(self.found.len()))
+    }
+}
+
+fn search(searcher: Searcher, pattern: &str, haystack: &str) -> Vec<(u64, String)> {
+    let regex = Regex::new(pattern).unwrap();
+    let mut sink = Collect::default();
+    searcher
+        .search_reader(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+    sink.found
+}
+
+fn owned(found: &[(u64, &str)]) -> Vec<(u64, String)> {
+    found.iter().map(|(o, s)| (*o, s.to_string())).collect()
+}
+
+#[test]
+fn test_matching_lines() {
+    let found = search(Searcher::new(), "o+", "uno\ndos\ntres\ncuatro");
+
+    assert_eq!(found, owned(&[(0, "uno\n"), (4, "dos\n"), (13, "cuatro")]));
+}
+
+#[test]
+fn test_one_result_per_line() {
+    let found = search(Searcher::new(), "a", "aaa\nbab\n");
+
+    assert_eq!(found, owned(&[(0, "aaa\n"), (4, "bab\n")]));
+}
+
+#[test]
+fn test_anchors_and_empty_lines() {
+    let found = search(Searcher::new(), "^$", "a\n\nb\n");
+    assert_eq!(found, owned(&[(2, "\n")]));
+
+    let found = search(Searcher::new(), "b$", "ab\nba\nb");
+    assert_eq!(found, owned(&[(0, "ab\n"), (6, "b")]));
+
+    assert!(search(Searcher::new(), "x*", "").is_empty());
+}
+
+#[test]
+fn test_match_never_spans_lines() {
+    // "[^x]" tambien matchea el '\n', pero eso no cuenta como match de la linea "x"
+    let found = search(Searcher::new(), "[^x]", "x\nxy\n");
+    assert_eq!(found, owned(&[(2, "xy\n")]));
+
+    let found = search(Searcher::new(), "b[[:space:]]c", "ab\ncd\nb c\n");
+    assert_eq!(found, owned(&[(6, "b c\n")]));
+}
+
+#[test]
+fn test_multi_line() {
+    let searcher = Searcher::new().multi_line(true);
+
+    let found = search(searcher, "b[[:space:]]c", "ab\ncd\nef\n");
+    assert_eq!(found, owned(&[(0, "ab\ncd\n")]));
+}
+
+#[test]
+fn test_sink_stops_search() {
+    let matcher = LiteralMatcher::new(&["a"]);
+    let mut sink = Collect {
+        limit: Some(2),
+        ..Collect::default()
+    };
+    Searcher::new()
+        .search_slice(&matcher, b"a\na\na\n", &mut sink)
+        .unwrap();
+
+    assert_eq!(sink.found.len(), 2);
+}
+
+#[test]
+fn test_invert_match() {
+    let searcher = Searcher::new().invert_match(true);
+
+    let found = search(searcher, "o", "uno\nab\n\ncd\ndos\nef");
+    assert_eq!(
+        found,
+        owned(&[(4, "ab\n"), (7, "\n"), (8, "cd\n"), (15, "ef")])
+    );
+
+    // Una linea que solo "matchea" cruzando el '\n' no cuenta como match
+    let found = search(searcher, "[^x]", "x\nxy\n");
+    assert_eq!(found, owned(&[(0, "x\n")]));
+
+    assert!(search(searcher, "x*", "a\nb\n").is_empty());
+}
+
+#[test]
+fn test_line_numbers() {
+    let regex = Regex::new("o").unwrap();
+    let haystack = "uno\nab\n\ndos\nef";
+
+    let mut sink = Collect::default();
+    Searcher::new()
+        .search_reader(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+    assert_eq!(sink.line_numbers, vec![None, None]);
+
+    let mut sink = Collect::default();
+    Searcher::new()
+        .line_number(true)
+        .search_reader(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+    assert_eq!(sink.line_numbers, vec![Some(1), Some(4)]);
+
+    let mut sink = Collect::default();
+    Searcher::new()
+        .line_number(true)
+        .invert_match(true)
+        .search_slice(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+    assert_eq!(sink.line_numbers, vec![Some(2), Some(3), Some(5)]);
+}
+
+#[test]
+fn test_line_numbers_across_buffers() {
+    // Suficientes lineas para que el lector tenga que volver a llenar el buffer varias veces
+    let haystack: String = (1..=50_000).map(|i| format!("linea {}\n", i)).collect();
+    let regex = Regex::new("^linea (1|25000|50000)$").unwrap();
+
+    let mut sink = Collect::default();
+    Searcher::new()
+        .line_number(true)
+        .search_reader(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+
+    assert_eq!(sink.line_numbers, vec![Some(1), Some(25000), Some(50000)]);
+    let offset = haystack.find("linea 25000\n").unwrap() as u64;
+    assert_eq!(sink.found[1], (offset, "linea 25000\n".to_string()));
+}
+
+/// Anota los resultados, el contexto y las separaciones en el orden en que llegan
+#[derive(Default)]
+struct Events {
+    events: Vec<String>,
+    limit: Option<usize>,
+}
+
+impl Sink for Events {
+    fn matched(&mut self, found: &SinkMatch) -> io::Result<bool> {
+        let text = String::from_utf8_lossy(found.bytes);
+        self.events.push(format!(
+            "{}:{}",
+            found.line_number.unwrap_or(0),
+            text.trim_end()
+        ));
+        let matches = self.events.iter().filter(|e| e.contains(':')).count();
+        Ok(self.limit != Some(matches))
+    }
+
+    fn context(&mut self, context: &SinkContext) -> io::Result<bool> {
+        let text = String::from_utf8_lossy(context.bytes);
+        let kind = match context.kind {
+            ContextKind::Before => 'b',
+            ContextKind::After => 'a',
+        };
+        let line_number = context.line_number.unwrap_or(0);
+        self.events
+            .push(format!("{}{}-{}", kind, line_number, text.trim_end()));
+        Ok(true)
+    }
+
+    fn context_break(&mut self) -> io::Result<bool> {
+        self.events.push("--".to_string());
+        Ok(true)
+    }
+}
+
+fn events(searcher: Searcher, pattern: &str, haystack: &str) -> Vec<String> {
+    let regex = Regex::new(pattern).unwrap();
+    let mut sink = Events::default();
+    searcher
+        .line_number(true)
+        .search_reader(&regex, haystack.as_bytes(), &mut sink)
+        .unwrap();
+    sink.events
+}
+
+#[test]
+fn test_context_lines() {
+    let haystack = "1\n2\n3\nx\n5\n6\n7\n8\nx\n10\n";
+
+    let found = events(Searcher::new().before_context(2), "x", haystack);
+    assert_eq!(found, ["b2-2", "b3-3", "4:x", "--", "b7-7", "b8-8", "9:x"]);
+
+    let found = events(Searcher::new().after_context(1), "x", haystack);
+    assert_eq!(found, ["4:x", "a5-5", "--", "9:x", "a10-10"]);
+
+    // Las ventanas que se tocan o se superponen se unen sin separador
+    let found = events(
+        Searcher::new().before_context(2).after_context(2),
+        "x",
+        haystack,
+    );
+    assert_eq!(
+        found,
+        ["b2-2", "b3-3", "4:x", "a5-5", "a6-6", "b7-7", "b8-8", "9:x", "a10-10"]
+    );
+
+    // Un resultado dentro del contexto de otro se entrega como resultado
+    let found = events(Searcher::new().after_context(3), "^[45]$", haystack);
+    assert_eq!(found, ["5:5", "a6-6", "a7-7", "a8-8"]);
+}
+
+#[test]
+fn test_context_with_invert_match() {
+    let searcher = Searcher::new().invert_match(true).before_context(1);
+
+    let found = events(searcher, "[0-9]", "1\n2\nx\n4\ny\n");
+    assert_eq!(found, ["b2-2", "3:x", "b4-4", "5:y"]);
+}
+
+#[test]
+fn test_trailing_context_after_stop() {
+    let regex = Regex::new("x").unwrap();
+    let mut sink = Events {
+        limit: Some(1),
+        ..Events::default()
+    };
+    Searcher::new()
+        .after_context(2)
+        .search_slice(&regex, b"x\nx\n3\n4\n", &mut sink)
+        .unwrap();
+
+    // Despues del limite, las lineas siguientes son contexto aunque matcheen
+    assert_eq!(sink.events, ["0:x", "a0-x", "a0-3"]);
+}
+
+#[test]
+fn test_context_across_buffers() {
+    let haystack: String = (1..=50_000).map(|i| format!("{}\n", i)).collect();
+    let searcher = Searcher::new().before_context(3000).after_context(1);
+
+    let found = events(searcher, "^(25000|25002)$", &haystack);
+    let expected: Vec<String> = (22000..25000)
+        .map(|i| format!("b{}-{}", i, i))
+        .chain(["25000:25000".to_string(), "a25001-25001".to_string()])
+        .chain(["25002:25002".to_string(), "a25003-25003".to_string()])
+        .collect();
+    assert_eq!(found, expected);
+}
+
+/// Guarda donde se detecto que la fuente es binaria, y si hay que seguir buscando en ella
+struct Binary {
+    offset: Option<u64>,
+    keep_searching: bool,
+    found: usize,
+}
+
+impl Sink for Binary {
+    fn matched(&mut self, _found: &SinkMatch) -> io::Result<bool> {
+        self.found += 1;
+        Ok(true)
+    }
+
+    fn binary_data(&mut self, offset: u64) -> io::Result<bool> {
+        self.offset = Some(offset);
+        Ok(self.keep_searching)
+    }
+}
+
+fn binary(searcher: Searcher, haystack: &[u8], keep_searching: bool) -> (Option<u64>, usize) {
+    let regex = Regex::new("o").unwrap();
+    let mut sink = Binary {
+        offset: None,
+        keep_searching,
+        found: 0,
+    };
+    let searcher = searcher.binary_detection(true);
+    searcher.search_slice(&regex, haystack, &mut sink).unwrap();
+    let from_slice = (sink.offset, sink.found);
+
+    // Leyendo de a bloques se detecta igual
+    sink.offset = None;
+    sink.found = 0;
+    searcher.search_reader(&regex, haystack, &mut sink).unwrap();
+    assert_eq!((sink.offset, sink.found), from_slice);
+    from_slice
+}
+
+#[test]
+fn test_binary_detection() {
+    let searcher = Searcher::new();
+    assert_eq!(binary(searcher, b"uno\ndos\n", true), (None, 2));
+    assert_eq!(binary(searcher, b"uno\nd\0os\n", true), (Some(5), 2));
+    assert_eq!(binary(searcher, b"uno\nd\0os\n", false), (Some(5), 0));
+    assert_eq!(binary(searcher, b"uno\n\xFF\xFEdos\n", true), (Some(4), 2));
+    assert_eq!(binary(searcher, "año\n".as_bytes(), true), (None, 1));
+
+    // Solo se revisa el principio de la fuente
+    let mut late = "uno\n".repeat(64 * 1024).into_bytes();
+    late.extend_from_slice(b"d\0o\n");
+    assert_eq!(binary(searcher, &late, true), (None, 64 * 1024 + 1));
+
+    // Sin la deteccion activa no se avisa
+    let regex = Regex::new("o").unwrap();
+    let mut sink = Binary {
+        offset: None,
+        keep_searching: false,
+        found: 0,
+    };
+    Searcher::new()
+        .search_slice(&regex, b"d\0os\n", &mut sink)
+        .unwrap();
+    assert_eq!((sink.offset, sink.found), (None, 1));
+}
@@ -0,0 +1,72 @@
+use grep::literal_matcher::LiteralMatcher;
+use grep::matcher::Matcher;
+use grep::regex::Regex;
+use grep::template::{Record, Template, TemplateError};
+
+/// Completa la plantilla con el primer match de `pattern` en `line`
+fn render(template: &str, pattern: &str, line: &str) -> String {
+    let regex = Regex::new(pattern).unwrap();
+    let template = Template::parse(template, &regex).unwrap();
+    let captures = regex.captures(line.as_bytes()).unwrap();
+    let start = captures.get(0).unwrap().start;
+    let record = Record {
+        path: "a.txt",
+        line_number: Some(3),
+        column: start + 1,
+        byte_offset: 100 + start as u64,
+        text: line.as_bytes(),
+        haystack: line.as_bytes(),
+        captures: Some(&captures),
+    };
+
+    let mut out = vec![];
+    template.render(&mut out, &record).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn test_placeholders() {
+    assert_eq!(
+        render("{path}:{line}:{col}:{byte}: {match}", "b+", "abbc"),
+        "a.txt:3:2:101: bb\n"
+    );
+    assert_eq!(render("[{text}]", "b", "abc"), "[abc]\n");
+    assert_eq!(
+        render("{1}/{2}/{clave}", "(?P<clave>[a-z]+)=([0-9]+)?", "x=1 y="),
+        "x/1/x\n"
+    );
+    // Un grupo que no participo del match queda vacio
+    assert_eq!(render("<{2}>", "([a-z]+)=([0-9]+)?", "y="), "<>\n");
+}
+
+#[test]
+fn test_escapes() {
+    assert_eq!(render("{line}\\t{match}\\n", "b", "abc"), "3\tb\n\n");
+    assert_eq!(render("\\{{match}\\}\\\\", "b", "abc"), "{b}\\\n");
+    // Una secuencia desconocida queda tal cual
+    assert_eq!(render("\\x{match}", "b", "abc"), "\\xb\n");
+}
+
+#[test]
+fn test_template_errors() {
+    let regex = Regex::new("(a)(?P<b>b)").unwrap();
+    let parse = |template: &str| Template::parse(template, &regex).map(|_| ());
+
+    assert_eq!(parse("{0}{1}{2}{b}"), Ok(()));
+    assert_eq!(parse("{match"), Err(TemplateError::UnclosedBrace));
+    assert_eq!(parse("match}"), Err(TemplateError::UnmatchedBrace));
+    assert_eq!(
+        parse("{3}"),
+        Err(TemplateError::UnknownPlaceholder("3".to_string()))
+    );
+    assert_eq!(
+        parse("{c}").unwrap_err().to_string(),
+        "invalid format: unknown placeholder '{c}'"
+    );
+
+    // Las cadenas fijas solo tienen el match completo
+    let literal = LiteralMatcher::new(&["ab"]);
+    assert_eq!(literal.captures_len(), 1);
+    assert!(Template::parse("{match}{0}", &literal).is_ok());
+    assert!(Template::parse("{1}", &literal).is_err());
+}